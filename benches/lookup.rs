@@ -0,0 +1,74 @@
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use minimal_perfect_hash::Builder;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+const N_KEYS: usize = 100_000;
+
+fn gen_keys() -> Vec<[u8; 16]> {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut keys: Vec<[u8; 16]> = (0..N_KEYS)
+        .map(|_| {
+            let mut k = [0u8; 16];
+            rng.fill_bytes(&mut k);
+            k
+        })
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+fn bench_lookup_16(c: &mut Criterion) {
+    let keys = gen_keys();
+    let mph = Builder::new().build_fixed(&keys).unwrap();
+
+    let mut group = c.benchmark_group("lookup_16b");
+    group.throughput(Throughput::Elements(keys.len() as u64));
+    group.bench_function("index", |b| {
+        b.iter(|| {
+            keys.iter()
+                .fold(0u64, |acc, k| acc ^ mph.index(black_box(&k[..])))
+        })
+    });
+    group.bench_function("index_unchecked", |b| {
+        b.iter(|| {
+            keys.iter().fold(0u64, |acc, k| {
+                // Safety: built just above.
+                acc ^ unsafe { mph.index_unchecked(black_box(&k[..])) }
+            })
+        })
+    });
+    group.bench_function("index_untrusted", |b| {
+        b.iter(|| {
+            keys.iter().fold(0u64, |acc, k| {
+                acc ^ mph.index_untrusted(black_box(&k[..])).unwrap_or(0)
+            })
+        })
+    });
+    group.bench_function("index_fixed", |b| {
+        b.iter(|| {
+            keys.iter()
+                .fold(0u64, |acc, k| acc ^ mph.index_fixed(black_box(k)))
+        })
+    });
+    group.bench_function("index_x2", |b| {
+        b.iter(|| {
+            keys.chunks_exact(2).fold(0u64, |acc, p| {
+                acc ^ mph.index(black_box(&p[0][..])) ^ mph.index(black_box(&p[1][..]))
+            })
+        })
+    });
+    group.bench_function("index_pair", |b| {
+        b.iter(|| {
+            keys.chunks_exact(2).fold(0u64, |acc, p| {
+                let (i, j) = mph.index_pair(black_box(&p[0][..]), black_box(&p[1][..]));
+                acc ^ i ^ j
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_lookup_16);
+criterion_main!(benches);
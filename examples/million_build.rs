@@ -21,28 +21,14 @@ fn main() -> Result<(), MphError> {
         N_KEYS as f64 / gen_s / 1e6
     );
 
-    // 2) Pre-hashing (BDZ vertices) — time measurement
-    //    Note: the builder will still perform hashing itself
-    // Use the same config as for build: salt matters for vertex derivation
+    // Gamma can be varied between 1.23..1.30 for a stable build on 1M keys.
     let cfg = BuildConfig {
-        // For stable build on 1M keys:
-        // gamma can be varied between 1.23..1.30
         gamma: 1.25,
         rehash_limit: 32,
         ..Default::default()
     };
-    let eff_salt = cfg.salt; // only for measurement; builder may shift salt during rehash
 
-    let t1 = Instant::now();
-    let _prehashed = prehash_vertices(&keys, eff_salt, (cfg.gamma * N_KEYS as f64).ceil() as u64);
-    let hash_s = t1.elapsed().as_secs_f64();
-    println!(
-        "hash:   {:>8.3} s   ({:.1} M keys/s)",
-        hash_s,
-        N_KEYS as f64 / hash_s / 1e6
-    );
-
-    // 3) Build MPH
+    // 2) Build MPH
     let t2 = Instant::now();
     let mph = Builder::new()
         .with_config(cfg)
@@ -54,15 +40,10 @@ fn main() -> Result<(), MphError> {
         N_KEYS as f64 / build_s / 1e6
     );
 
-    // 4) Lookup all keys
+    // 3) Lookup all keys
     let t3 = Instant::now();
-    // Split into chunks to avoid compiler removing the loop and to avoid cache overheating
-    let mut acc: u64 = 0;
-    for chunk in keys.chunks(32_768) {
-        for k in chunk {
-            acc ^= mph.index(k);
-        }
-    }
+    let indices = mph.resolve_all(&keys);
+    let acc = indices.iter().fold(0u64, |acc, &i| acc ^ i);
     let lookup_s = t3.elapsed().as_secs_f64();
     println!(
         "lookup: {:>8.3} s   ({:.1} M lookups/s)   (acc={acc})",
@@ -70,10 +51,39 @@ fn main() -> Result<(), MphError> {
         N_KEYS as f64 / lookup_s / 1e6
     );
 
+    // 4) Lookup stages in isolation (hash only, hash + g loads); needs `--features bench`
+    #[cfg(feature = "bench")]
+    {
+        let t4 = Instant::now();
+        let mut acc: u32 = 0;
+        for k in &keys {
+            let (a, b, c) = mph.index_vertices(k);
+            acc ^= a ^ b ^ c;
+        }
+        let hash_s = t4.elapsed().as_secs_f64();
+        println!(
+            "  hash:   {:>8.3} s   ({:.1} M keys/s)   (acc={acc})",
+            hash_s,
+            N_KEYS as f64 / hash_s / 1e6
+        );
+
+        let t5 = Instant::now();
+        let mut acc: u32 = 0;
+        for k in &keys {
+            acc ^= mph.index_gsum(k);
+        }
+        let gsum_s = t5.elapsed().as_secs_f64();
+        println!(
+            "  g sum:  {:>8.3} s   ({:.1} M keys/s)   (acc={acc})",
+            gsum_s,
+            N_KEYS as f64 / gsum_s / 1e6
+        );
+    }
+
     println!("----------------------------------------------");
     println!(
-        "Total (gen + hash + build + lookup): {:.3} s",
-        gen_s + hash_s + build_s + lookup_s
+        "Total (gen + build + lookup): {:.3} s",
+        gen_s + build_s + lookup_s
     );
 
     Ok(())
@@ -93,28 +103,3 @@ fn gen_unique_keys(n: usize, seed: u64) -> Vec<Vec<u8>> {
     }
     keys
 }
-
-/// Precompute vertices (only for profiling hashing time).
-/// This reproduces exactly the same formula as inside BDZ:
-/// three independent XXH3 with different seeds, then `% m`.
-fn prehash_vertices(keys: &[Vec<u8>], salt: u64, m: u64) -> Vec<(u32, u32, u32)> {
-    use xxhash_rust::xxh3::xxh3_64_with_seed;
-
-    // Seeds must match those used by the library
-    #[inline]
-    fn verts(key: &[u8], salt: u64, m: u64) -> (u32, u32, u32) {
-        let s1 = salt ^ 0x9E37_79B9_7F4A_7C15;
-        let s2 = salt.wrapping_mul(0xA24B_1F6F);
-        let s3 = salt ^ 0x853C_49E6_0A6C_9D39;
-        let a = xxh3_64_with_seed(key, s1) % m;
-        let b = xxh3_64_with_seed(key, s2) % m;
-        let c = xxh3_64_with_seed(key, s3) % m;
-        (a as u32, b as u32, c as u32)
-    }
-
-    let mut out = Vec::with_capacity(keys.len());
-    for k in keys {
-        out.push(verts(k, salt, m));
-    }
-    out
-}
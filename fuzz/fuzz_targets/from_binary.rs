@@ -0,0 +1,22 @@
+//! `cargo fuzz run from_binary`: the `binary` decoder must reject malformed bytes
+//! cleanly, and whatever it accepts must be safe to query, unchecked lookups included.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minimal_perfect_hash::Mphf;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(mph) = Mphf::from_binary(data) else {
+        return;
+    };
+    for key in [&b""[..], b"fuzz", data] {
+        let i = mph.index(key);
+        assert!(mph.contains_index(i));
+        // Safety: `from_binary` only returns instances holding the build invariants.
+        assert_eq!(unsafe { mph.index_unchecked(key) }, i);
+        let _ = mph.index_if_member(key);
+        let _ = mph.index_untrusted(key);
+    }
+    assert_eq!(mph.to_binary(), data);
+});
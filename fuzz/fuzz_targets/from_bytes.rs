@@ -0,0 +1,21 @@
+//! `cargo fuzz run from_bytes`: decoding arbitrary bytes must fail cleanly, and
+//! whatever does decode must be safe to query, unchecked lookups included.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minimal_perfect_hash::Mphf;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(mph) = Mphf::from_bytes(data) else {
+        return;
+    };
+    for key in [&b""[..], b"fuzz", data] {
+        let i = mph.index(key);
+        assert!(mph.contains_index(i));
+        // Safety: `from_bytes` only returns instances holding the build invariants.
+        assert_eq!(unsafe { mph.index_unchecked(key) }, i);
+        let _ = mph.index_if_member(key);
+        let _ = mph.index_untrusted(key);
+    }
+});
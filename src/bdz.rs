@@ -1,131 +1,1957 @@
+use crate::concat::{ConcatMphf, partition_of};
+use crate::streaming::KeyArena;
+use crate::util::{BitSet, get_at, get_at_mut};
+use crate::wyhash_fixed::wyhash_fixed;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
-use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
 use thiserror::Error;
 
 /// Minimal perfect hash by BDZ (3-hypergraph peeling) with:
 /// - wyhash-based vertex derivation (1×wyhash + splitmix64)
 /// - CSR adjacency (offsets + flat edges)
-/// - optional parallel hashing via rayon ("rayon" feature)
+/// - optional parallel hashing and dedup via rayon ("parallel" feature)
 /// - u32 everywhere and cache-friendly data layout
 ///
 /// Query: f(k) = (g[v0] + g[v1] + g[v2]) % n
+///
+/// Sets below 32 keys skip the graph: `g` holds the keys' sorted
+/// 32-bit hashes (`m == n`, which a BDZ build never produces) and `index` is the
+/// hash's rank, counted branch-free.
+///
+/// `Mphf` is `Send + Sync`: lookups only read `g` and involve no interior mutability,
+/// so a single instance can serve concurrent `index` calls (e.g. behind an `Arc`).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "MphfParts"))]
 #[derive(Debug, Clone)]
 pub struct Mphf {
-    pub n: u64,      // number of keys
+    pub n: u64,      // number of keys (`index` caches a reduction constant for it)
     pub m: u32,      // graph vertices (m = ceil(gamma * n))
     pub salt: u64,   // effective salt used to derive vertices
-    pub g: Vec<u32>, // length == m, values in [0..n)
+    pub g: Vec<u32>, // length == m, values in [0..n) (sorted hashes when m == n)
+    /// Optional 1-byte fingerprint per index (see `BuildConfig::fingerprint`).
+    /// When absent it costs a single tag byte in the serialized form.
+    pub fingerprints: Option<Vec<u8>>, // length == range()
+    /// False for builds with `BuildConfig::require_minimal = false`, whose indices
+    /// span `[0, m)` instead of `[0, n)`.
+    pub minimal: bool,
+    /// Index renumbering set by [`apply_permutation`](Mphf::apply_permutation):
+    /// lookups return `remap[raw]` instead of `raw`. `None` (1 tag byte) otherwise.
+    pub remap: Option<Vec<u32>>, // length == range()
+    /// Lemire's fastmod constant for `n`, replacing the lookup's `% n` with two
+    /// multiplies. Derived from `n`, so it is recomputed rather than serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fastmod: u64,
+    /// `m == n`: `g` is a small set's sorted hash table rather than a BDZ `g`. Only
+    /// sets below 32 keys have that shape (see [`check_shape`]).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    small: bool,
+}
+
+/// `Mphf` minus its derived fields: the serialized form, and the one way to assemble one.
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub(crate) struct MphfParts {
+    pub(crate) n: u64,
+    pub(crate) m: u32,
+    pub(crate) salt: u64,
+    pub(crate) g: Vec<u32>,
+    pub(crate) fingerprints: Option<Vec<u8>>,
+    pub(crate) minimal: bool,
+    pub(crate) remap: Option<Vec<u32>>,
+}
+
+impl From<MphfParts> for Mphf {
+    fn from(p: MphfParts) -> Self {
+        Mphf {
+            fastmod: fastmod_constant(p.n as u32),
+            small: p.m as u64 == p.n,
+            n: p.n,
+            m: p.m,
+            salt: p.salt,
+            g: p.g,
+            fingerprints: p.fingerprints,
+            minimal: p.minimal,
+            remap: p.remap,
+        }
+    }
+}
+
+// Compile-time guard for the `Send + Sync` guarantee above.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Mphf>();
+};
+
+impl Mphf {
+    /// Effective salt the vertices were derived with (base salt mixed with the round).
+    #[inline]
+    pub fn salt(&self) -> u64 {
+        self.salt
+    }
+
+    /// The 64-bit hash every lookup of `key` starts from: [`mph_hash`]`(key, self.salt())`,
+    /// pinned by [`HASH_VERSION`]. Key secondary structures (filters, shards) by it to
+    /// agree with this MPH on key identity. Unrelated to the 1-byte fingerprints of
+    /// [`index_if_member`](Self::index_if_member), which use a separate seed.
+    #[inline]
+    pub fn key_fingerprint(&self, key: &[u8]) -> u64 {
+        wyhash1(key, self.salt)
+    }
+
+    /// Index of `key`. Every table access is bounds-checked, so this is sound for any
+    /// `Mphf`, including a tampered or hand-assembled one (which may panic or return
+    /// garbage, but never reads out of bounds). Foreign keys map to arbitrary indices.
+    #[inline]
+    pub fn index(&self, key: &[u8]) -> u64 {
+        self.resolve(wyhash1(key, self.salt))
+    }
+
+    /// [`index`](Self::index) without the bounds checks, for hot loops over trusted
+    /// instances. The checks are cheap next to the three dependent `g` loads: the
+    /// two are within noise of each other in `benches/lookup.rs` (100k 16-byte keys).
+    ///
+    /// # Safety
+    /// `self` must hold the build invariants: `g.len() == m`, `remap` (if any) of
+    /// length [`range`](Self::range) with entries below it, and `n`, `m` as built.
+    /// That holds for an `Mphf` built in-process or decoded by `from_binary`,
+    /// `from_bytes` or `read_from`, and not necessarily for one from another serde
+    /// format or with fields edited by hand.
+    #[inline]
+    pub unsafe fn index_unchecked(&self, key: &[u8]) -> u64 {
+        // Safety: forwarded to the caller.
+        unsafe { self.resolve_unchecked(wyhash1(key, self.salt)) }
+    }
+    #[inline]
+    pub fn index_str(&self, s: &str) -> u64 {
+        self.index(s.as_bytes())
+    }
+
+    /// Generic front end over [`index`](Self::index) for `String`, `Vec<u8>`, arrays, etc.
+    #[inline]
+    pub fn index_key(&self, key: impl AsRef<[u8]>) -> u64 {
+        self.index(key.as_ref())
+    }
+
+    /// [`index`](Self::index) as `u32`. Lossless: indices are below `range() <= m`,
+    /// and `m` is a `u32`.
+    #[inline]
+    pub fn index_u32(&self, key: &[u8]) -> u32 {
+        self.index(key) as u32
+    }
+
+    /// [`index`](Self::index) converted to `T`, e.g. `usize` or `u16`.
+    /// Panics if the index does not fit in `T`; check `range()` up front to rule that out.
+    #[inline]
+    pub fn index_as<T: TryFrom<u64>>(&self, key: &[u8]) -> T {
+        let i = self.index(key);
+        T::try_from(i).unwrap_or_else(|_| panic!("index {i} does not fit the target type"))
+    }
+
+    /// Lookup for fixed-size keys. Same result as `index(&key[..])`, but the hash is
+    /// specialized on `N` so the length-dependent branches compile away.
+    #[inline]
+    pub fn index_fixed<const N: usize>(&self, key: &[u8; N]) -> u64 {
+        self.resolve(wyhash_fixed(key, self.salt))
+    }
+
+    /// Hint the CPU to fetch the three `g` entries `key` will read, without reading
+    /// them. Issue it a few keys ahead (`prefetch(next); index(cur)`) to overlap the
+    /// cache misses of a large table. The vertices are hashed twice that way, so this
+    /// only pays off once `g` outgrows the cache: on a 100k-key table (~0.5 MB) it
+    /// lowers throughput. A no-op on targets other than x86_64.
+    #[inline]
+    pub fn prefetch(&self, key: &[u8]) {
+        self.prefetch_base(wyhash1(key, self.salt));
+    }
+
+    /// [`prefetch`](Self::prefetch) for a key whose base hash is already known.
+    #[inline]
+    fn prefetch_base(&self, base: u64) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            use std::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+            let (a, b, c) = vertices_from_base(base, self.m as u64);
+            for v in [a, b, c] {
+                // A prefetch never faults; the address is in bounds anyway (v < m).
+                let p = self.g.as_ptr().wrapping_add(v as usize) as *const i8;
+                unsafe { _mm_prefetch::<_MM_HINT_T0>(p) };
+            }
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        let _ = base;
+    }
+
+    /// Indices of `keys` in input order: `out[i] == self.index(keys[i])`, so for the
+    /// build set of a minimal MPH the result is a permutation of `[0, n)`.
+    ///
+    /// Keys are hashed a batch at a time and the batch's `g` entries prefetched before
+    /// any of them is resolved, so the cache misses overlap without hashing each key
+    /// twice as a [`prefetch`](Self::prefetch) loop would. On 10M 16-byte keys that
+    /// took ~36 ns per key against ~48 for an `index` loop; below a 1 MiB `g` it skips
+    /// the prefetches and matches the loop.
+    pub fn resolve_all<K: AsRef<[u8]>>(&self, keys: &[K]) -> Vec<u64> {
+        const BATCH: usize = 16;
+        let mut out = Vec::with_capacity(keys.len());
+        let mut bases = [0u64; BATCH];
+        let prefetch = !self.small && self.g.len() >= PREFETCH_MIN_G;
+        for chunk in keys.chunks(BATCH) {
+            for (base, k) in bases.iter_mut().zip(chunk) {
+                *base = wyhash1(k.as_ref(), self.salt);
+                if prefetch {
+                    self.prefetch_base(*base);
+                }
+            }
+            out.extend(bases[..chunk.len()].iter().map(|&b| self.resolve(b)));
+        }
+        out
+    }
+
+    /// `(self.index(a), self.index(b))`, with both keys hashed before either
+    /// touches `g` so the six loads overlap instead of forming two dependent chains,
+    /// e.g. to resolve both sides of a join key at once. In `benches/lookup.rs` it ran
+    /// 5–25% ahead of two `index` calls (100k keys, noisy), and ~15% ahead on 10M;
+    /// an out-of-order core already overlaps much of two independent calls.
+    #[inline]
+    pub fn index_pair(&self, a: &[u8], b: &[u8]) -> (u64, u64) {
+        let (base_a, base_b) = (wyhash1(a, self.salt), wyhash1(b, self.salt));
+        if self.small {
+            return (self.resolve(base_a), self.resolve(base_b));
+        }
+        let m = self.m as u64;
+        let (va, vb) = (vertices_from_base(base_a, m), vertices_from_base(base_b, m));
+        let (sum_a, sum_b) = (self.g_sum(va), self.g_sum(vb));
+        (self.finish(va, sum_a), self.finish(vb, sum_b))
+    }
+
+    /// Profiling stage 1 of `index`: hashing only, no `g` loads.
+    #[cfg(feature = "bench")]
+    #[inline]
+    pub fn index_vertices(&self, key: &[u8]) -> (u32, u32, u32) {
+        vertices(key, self.salt, self.m as u64)
+    }
+
+    /// Profiling stages 1–2 of `index`: hashing and the three `g` loads, without the
+    /// final reduction `% n`.
+    #[cfg(feature = "bench")]
+    #[inline]
+    pub fn index_gsum(&self, key: &[u8]) -> u32 {
+        let (a, b, c) = vertices(key, self.salt, self.m as u64);
+        // Safety: a,b,c < m; g.len() == m
+        unsafe {
+            (*get_at(&self.g, a as usize))
+                .wrapping_add(*get_at(&self.g, b as usize))
+                .wrapping_add(*get_at(&self.g, c as usize))
+        }
+    }
+
+    /// Lookup for instances from an untrusted source (e.g. bytes off the network).
+    ///
+    /// `index` is sound on such input too, but panics when a table is too short. This
+    /// variant widens the sum and uses a plain `% n`, returning `None` instead of
+    /// panicking. Expect ~15% lower throughput than `index_unchecked`
+    /// (`benches/lookup.rs`, 16-byte keys).
+    #[inline]
+    pub fn index_untrusted(&self, key: &[u8]) -> Option<u64> {
+        if self.n == 0 || self.m == 0 {
+            return None;
+        }
+        let base = wyhash1(key, self.salt);
+        let raw = if self.small {
+            Some(small_rank(&self.g, base) as u64).filter(|&r| r < self.n)?
+        } else {
+            let (a, b, c) = vertices_from_base(base, self.m as u64);
+            let g = |v: u32| self.g.get(v as usize).map(|&x| x as u64);
+            let sum = g(a)? + g(b)? + g(c)?;
+            if self.minimal {
+                sum % self.n
+            } else {
+                [a, b, c][(sum % 3) as usize] as u64
+            }
+        };
+        match &self.remap {
+            Some(remap) => remap.get(raw as usize).map(|&i| i as u64),
+            None => Some(raw),
+        }
+    }
+
+    /// Index for a key whose `wyhash1` under `salt` is `base`.
+    #[inline]
+    fn resolve(&self, base: u64) -> u64 {
+        if self.small {
+            return self.remapped(small_rank(&self.g, base));
+        }
+        let v = vertices_from_base(base, self.m as u64);
+        self.finish(v, self.g_sum(v))
+    }
+
+    #[inline]
+    fn g_sum(&self, (a, b, c): (u32, u32, u32)) -> u32 {
+        self.g[a as usize]
+            .wrapping_add(self.g[b as usize])
+            .wrapping_add(self.g[c as usize])
+    }
+
+    /// Index of the key with vertices `(a, b, c)`, given the sum of their `g` entries.
+    #[inline]
+    fn finish(&self, (a, b, c): (u32, u32, u32), sum: u32) -> u64 {
+        let raw = if self.minimal {
+            fastmod(sum, self.fastmod, self.n as u32)
+        } else {
+            [a, b, c][(sum % 3) as usize]
+        };
+        self.remapped(raw)
+    }
+
+    #[inline]
+    fn remapped(&self, raw: u32) -> u64 {
+        match &self.remap {
+            Some(remap) => remap[raw as usize] as u64,
+            None => raw as u64,
+        }
+    }
+
+    /// [`resolve`](Self::resolve) trusting the build invariants.
+    ///
+    /// # Safety
+    /// As for [`index_unchecked`](Self::index_unchecked).
+    #[inline]
+    unsafe fn resolve_unchecked(&self, base: u64) -> u64 {
+        if self.small {
+            return self.remapped(small_rank(&self.g, base));
+        }
+        let (a, b, c) = vertices_from_base(base, self.m as u64);
+        // Safety: a,b,c < m; g.len() == m
+        let ga = unsafe { *get_at(&self.g, a as usize) };
+        let gb = unsafe { *get_at(&self.g, b as usize) };
+        let gc = unsafe { *get_at(&self.g, c as usize) };
+        let sum = ga.wrapping_add(gb).wrapping_add(gc);
+        let raw = if self.minimal {
+            fastmod(sum, self.fastmod, self.n as u32)
+        } else {
+            // g[] holds the position (mod 3) of the vertex owned by the key's edge.
+            [a, b, c][(sum % 3) as usize]
+        };
+        match &self.remap {
+            // Safety: raw < range() == remap.len()
+            Some(remap) => unsafe { *get_at(remap, raw as usize) as u64 },
+            None => raw as u64,
+        }
+    }
+
+    /// Renumber indices so that afterwards `index(k) == perm[old index(k)]`, e.g. to
+    /// move hot keys to low indices. `perm` must be a permutation of `[0, range())`.
+    ///
+    /// The permutation cannot be folded into `g`: each key's index is pinned by the
+    /// peeling order of the build, which needs the keys to redo. Instead it is kept as
+    /// an indirection table (composed with any earlier one), adding 4 bytes per index
+    /// and one dependent load per lookup. Fingerprints are moved along.
+    pub fn apply_permutation(&mut self, perm: &[u32]) -> Result<(), MphError> {
+        let range = self.range() as usize;
+        if perm.len() != range {
+            return Err(MphError::InvalidConfig(format!(
+                "permutation has {} entries, expected {range}",
+                perm.len()
+            )));
+        }
+        let mut hit = vec![false; range];
+        for &p in perm {
+            match hit.get_mut(p as usize) {
+                Some(h) if !*h => *h = true,
+                _ => {
+                    return Err(MphError::InvalidConfig(format!(
+                        "not a permutation of [0, {range}): {p} is out of range or repeated"
+                    )));
+                }
+            }
+        }
+        let remap = match self.remap.take() {
+            Some(old) => old.iter().map(|&i| perm[i as usize]).collect(),
+            None => perm.to_vec(),
+        };
+        if let Some(fps) = &mut self.fingerprints {
+            let mut moved = vec![0u8; range];
+            for (i, &fp) in fps.iter().enumerate() {
+                moved[perm[i] as usize] = fp;
+            }
+            *fps = moved;
+        }
+        self.remap = Some(remap);
+        Ok(())
+    }
+
+    /// The raw `g` table (`m` entries, each in `[0, n)`), if stored unpacked.
+    /// `None` for sets below 32 keys, whose `g` field holds sorted key hashes instead;
+    /// prefer this over the `g` field, which may change representation if `g` becomes
+    /// bit-packed.
+    #[inline]
+    pub fn g_slice(&self) -> Option<&[u32]> {
+        (!self.small).then_some(&self.g)
+    }
+
+    /// Release excess capacity of `g`, the fingerprints and the remap table. Builds
+    /// already allocate them at their exact size; this matters for instances assembled
+    /// or mutated by hand.
+    pub fn shrink_to_fit(&mut self) {
+        self.g.shrink_to_fit();
+        if let Some(fps) = &mut self.fingerprints {
+            fps.shrink_to_fit();
+        }
+        if let Some(remap) = &mut self.remap {
+            remap.shrink_to_fit();
+        }
+    }
+
+    /// Whether indices cover exactly `[0, n)` (minimal) rather than `[0, m)`.
+    /// Only false for builds with `BuildConfig::require_minimal = false`.
+    #[inline]
+    pub fn is_minimal(&self) -> bool {
+        self.minimal
+    }
+
+    /// Exclusive upper bound of `index`: `n` when minimal, `m` otherwise. Arrays
+    /// indexed by `index` must have this length.
+    #[inline]
+    pub fn range(&self) -> u64 {
+        if self.minimal { self.n } else { self.m as u64 }
+    }
+
+    /// Whether `idx` is an index this MPH can return, i.e. `idx < range()`
+    /// (`idx < n` for minimal builds).
+    #[inline]
+    pub fn contains_index(&self, idx: u64) -> bool {
+        idx < self.range()
+    }
+
+    /// Every index this MPH can return: `0..range()`.
+    #[inline]
+    pub fn index_range(&self) -> std::ops::Range<u64> {
+        0..self.range()
+    }
+
+    /// Vertex ratio `m / n` actually used. May exceed the configured `gamma`
+    /// due to rounding, `prime_m`, and the padding of sets below 256 keys to
+    /// `gamma = 1.5` (see [`BuildStats::padded`]); exactly 1 for sets below 32 keys,
+    /// which store no graph.
+    #[inline]
+    pub fn achieved_gamma(&self) -> f64 {
+        self.m as f64 / self.n as f64
+    }
+
+    /// Like [`index`](Self::index), but returns `None` when the key's fingerprint does
+    /// not match, rejecting ~255/256 of non-members. Without fingerprints (see
+    /// `BuildConfig::fingerprint`) every key is reported as a member.
+    #[inline]
+    pub fn index_if_member(&self, key: &[u8]) -> Option<u64> {
+        let i = self.index(key);
+        match &self.fingerprints {
+            Some(fps) if fps[i as usize] != fingerprint_byte(key, self.salt) => None,
+            _ => Some(i),
+        }
+    }
+
+    /// [`index_if_member`](Self::index_if_member) for string keys, matching
+    /// [`index_str`](Self::index_str). Only rejects anything when the MPH was built
+    /// with `BuildConfig::fingerprint`.
+    #[inline]
+    pub fn index_if_member_str(&self, s: &str) -> Option<u64> {
+        self.index_if_member(s.as_bytes())
+    }
+
+    /// [`index_if_member`](Self::index_if_member) with `default` for rejected keys.
+    /// Without fingerprints this always returns the computed index.
+    #[inline]
+    pub fn index_or(&self, key: &[u8], default: u64) -> u64 {
+        self.index_if_member(key).unwrap_or(default)
+    }
+
+    /// Lazily pair every key with its index.
+    pub fn enumerate<'a>(&'a self, keys: &'a [&'a [u8]]) -> impl Iterator<Item = (&'a [u8], u64)> {
+        keys.iter().map(move |&k| (k, self.index(k)))
+    }
+
+    /// Lazily map keys from any iterator to their indices, e.g.
+    /// `mph.index_iter(lines.map(str::as_bytes)).collect::<Vec<_>>()`.
+    pub fn index_iter<'a, I>(&'a self, keys: I) -> impl Iterator<Item = u64> + 'a
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+        I::IntoIter: 'a,
+    {
+        keys.into_iter().map(move |k| self.index(k))
+    }
+
+    /// New MPH over the keys of the build set `keys` for which `keep` returns true.
+    ///
+    /// The `Mphf` holds no keys, so this is a fresh build over the survivors with
+    /// default `gamma` and salt, carrying over only whether the build is minimal and
+    /// has fingerprints. Indices are **not** preserved: the result numbers the kept
+    /// keys `[0, kept)` (when minimal) in its own order, so values indexed by `self`
+    /// must be re-laid out (e.g. via [`to_index_map`](Self::to_index_map)).
+    /// Fails with `EmptyKeySet` if no key is kept.
+    pub fn filter_rebuild(
+        &self,
+        keys: &[&[u8]],
+        keep: impl Fn(&[u8]) -> bool,
+    ) -> Result<Mphf, MphError> {
+        assert_eq!(
+            keys.len() as u64,
+            self.n,
+            "keys must be the MPH's build set"
+        );
+        let kept: Vec<&[u8]> = keys.iter().copied().filter(|k| keep(k)).collect();
+        if kept.is_empty() {
+            return Err(MphError::EmptyKeySet);
+        }
+        let cfg = BuildConfig {
+            fingerprint: self.fingerprints.is_some(),
+            require_minimal: self.minimal,
+            ..Default::default()
+        };
+        Builder::new().with_config(cfg).build(kept)
+    }
+
+    /// Redo only the `g` assignment of this build, e.g. to switch between minimal and
+    /// non-minimal (`BuildConfig::require_minimal`) without searching for a salt again.
+    ///
+    /// What each part of a build depends on, and so survives which change:
+    /// - the vertices of a key depend on `salt` and `m` only. Changing `gamma` or the
+    ///   key count changes `m`, and with it every vertex (see [`prehash`]);
+    /// - whether the graph peels, and in which order, depends on the vertices only, so
+    ///   the same `salt` and `m` are guaranteed to peel again;
+    /// - `g` depends on the peel order and on `minimal`, and is what this recomputes.
+    ///
+    /// `keys` must be the build set, in any order. The vertices are derived again (the
+    /// `Mphf` does not keep them), but no other salt is tried and the result keeps
+    /// `salt` and `m`: `reassign_g(keys, self.is_minimal())` reproduces `g` (unless the
+    /// build used `low_memory_peel`, whose peel order differs). Fingerprints are
+    /// recomputed for the new indices; a permutation applied with
+    /// [`apply_permutation`](Self::apply_permutation) is dropped. Sets below 32 keys
+    /// store no graph and stay minimal: for them only those last two steps happen.
+    pub fn reassign_g(&self, keys: &[&[u8]], minimal: bool) -> Result<Mphf, MphError> {
+        assert_eq!(
+            keys.len() as u64,
+            self.n,
+            "keys must be the MPH's build set"
+        );
+        let mut mph = if self.small {
+            MphfParts {
+                n: self.n,
+                m: self.m,
+                salt: self.salt,
+                g: self.g.clone(),
+                fingerprints: None,
+                remap: None,
+                minimal: true,
+            }
+            .into()
+        } else {
+            let (v0, v1, v2) = derive_vertices(keys, self.salt, self.m as u64);
+            build_from_edges(
+                [&v0, &v1, &v2],
+                keys.len(),
+                self.m,
+                self.salt,
+                minimal,
+                &mut GraphScratch::default(),
+            )?
+        };
+        if self.fingerprints.is_some() {
+            mph.fingerprints = Some(fingerprint_table(&mph, keys.len(), &|i| keys[i]));
+        }
+        Ok(mph)
+    }
+
+    /// Inverse permutation: `out[i]` is the position in `keys` of the key mapped to `i`,
+    /// so `values[out[i]]` reorders a parallel value array into index order.
+    /// `keys` must be exactly the build set. `out` has [`range`](Self::range) entries;
+    /// for non-minimal builds the unused ones are `usize::MAX`.
+    pub fn to_index_map(&self, keys: &[&[u8]]) -> Vec<usize> {
+        assert_eq!(
+            keys.len() as u64,
+            self.n,
+            "keys must be the MPH's build set"
+        );
+        let mut out = vec![usize::MAX; self.range() as usize];
+        for (pos, &k) in keys.iter().enumerate() {
+            out[self.index(k) as usize] = pos;
+        }
+        out
+    }
+
+    /// Map every key of `keys` and report how the indices cover [`range`](Self::range),
+    /// for CI output that says what went wrong instead of a bare `false` or panic.
+    /// Stops at the first collision, so `checked` counts the keys mapped before it.
+    pub fn validate_against(&self, keys: &[&[u8]]) -> ValidationReport {
+        let range = self.range();
+        let mut seen = BitSet::new(range as usize);
+        let mut report = ValidationReport {
+            checked: 0,
+            covers_range: false,
+            min_index: None,
+            max_index: None,
+            first_collision: None,
+        };
+        for (pos, &k) in keys.iter().enumerate() {
+            let i = self.index(k);
+            report.checked += 1;
+            report.min_index = Some(report.min_index.map_or(i, |m| m.min(i)));
+            report.max_index = Some(report.max_index.map_or(i, |m| m.max(i)));
+            if seen.test(i as usize) {
+                // Only the bits are tracked; find the earlier owner once, here.
+                let first = keys.iter().position(|&o| self.index(o) == i).unwrap();
+                report.first_collision = Some(IndexCollision {
+                    index: i,
+                    first,
+                    second: pos,
+                });
+                return report;
+            }
+            seen.set(i as usize);
+        }
+        report.covers_range = report.checked as u64 == range;
+        report
+    }
+
+    /// Serialize into a `Vec` ([`write_to`](Self::write_to) over an in-memory buffer).
+    #[cfg(feature = "serde")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MphError> {
+        let mut out = Vec::with_capacity(1 + bincode::serialized_size(self)? as usize);
+        self.write_to(&mut out)?;
+        Ok(out)
+    }
+
+    /// Deserialize from a byte slice ([`read_from`](Self::read_from) over it).
+    ///
+    /// Safe on arbitrary input (`fuzz/fuzz_targets/from_bytes.rs`): decoding is capped
+    /// at `bytes.len()`, so a bogus length field fails instead of allocating it.
+    #[cfg(feature = "serde")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MphError> {
+        Self::decode(bytes, bytes.len() as u64)
+    }
+
+    /// Serialize straight into `w`, e.g. a `File`, without building the whole blob in
+    /// memory first: `g` is encoded element by element through a 64 KiB buffer, so
+    /// saving needs no more than the `Mphf` itself. Same bytes as [`to_bytes`](Self::to_bytes).
+    ///
+    /// The blob starts with one byte holding [`HASH_VERSION`], followed by the bincode
+    /// encoding of the fields.
+    #[cfg(feature = "serde")]
+    pub fn write_to(&self, w: impl std::io::Write) -> Result<(), MphError> {
+        let mut w = std::io::BufWriter::with_capacity(1 << 16, w);
+        std::io::Write::write_all(&mut w, &[HASH_VERSION as u8])
+            .map_err(|e| MphError::Serde(e.into()))?;
+        bincode::serialize_into(&mut w, self)?;
+        std::io::Write::flush(&mut w).map_err(|e| MphError::Serde(e.into()))
+    }
+
+    /// Counterpart of [`write_to`](Self::write_to). Reads exactly one `Mphf` and stops,
+    /// so `r` may carry other data after it; for that reason `r` is not buffered here,
+    /// so wrap a `File` in a `BufReader` to avoid many small reads.
+    ///
+    /// Blobs stamped with a hash version this build does not implement are rejected
+    /// with [`MphError::UnsupportedHashVersion`] instead of yielding wrong indices, and
+    /// decoded fields that contradict each other (e.g. `g.len() != m`) with
+    /// [`MphError::Corrupt`], so the result is safe for
+    /// [`index_unchecked`](Self::index_unchecked). A bogus length field allocates at
+    /// most about 1 MiB ahead of the data actually read before the stream runs dry.
+    #[cfg(feature = "serde")]
+    pub fn read_from(r: impl std::io::Read) -> Result<Self, MphError> {
+        Self::decode(r, u64::MAX)
+    }
+
+    /// [`read_from`](Self::read_from), reading at most `limit` bytes past the version.
+    #[cfg(feature = "serde")]
+    fn decode(mut r: impl std::io::Read, limit: u64) -> Result<Self, MphError> {
+        use bincode::Options;
+
+        let mut version = [0u8];
+        r.read_exact(&mut version)
+            .map_err(|e| MphError::Serde(e.into()))?;
+        // Same encoding as `bincode::serialize`, plus the limit.
+        let opts = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(limit);
+        // Each version decodes to the lookup path it was built with. Only version 1
+        // exists so far; a new hash must keep this arm resolving old blobs.
+        let mph: Mphf = match version[0] {
+            1 => opts.deserialize_from(r)?,
+            v => return Err(MphError::UnsupportedHashVersion(v)),
+        };
+        mph.check_invariants().map_err(MphError::Corrupt)?;
+        Ok(mph)
+    }
+
+    /// The build invariants `index_unchecked` relies on, as `from_binary` checks them.
+    #[cfg(feature = "serde")]
+    fn check_invariants(&self) -> Result<(), &'static str> {
+        check_shape(self.n, self.m)?;
+        if self.g.len() != self.m as usize {
+            return Err("g length does not match m");
+        }
+        let range = self.range() as usize;
+        if self.fingerprints.as_ref().is_some_and(|f| f.len() != range) {
+            return Err("fingerprint count does not match the index range");
+        }
+        let in_range = |remap: &Vec<u32>| remap.iter().all(|&i| (i as usize) < range);
+        if self
+            .remap
+            .as_ref()
+            .is_some_and(|r| r.len() != range || !in_range(r))
+        {
+            return Err("remap is not a table over the index range");
+        }
+        Ok(())
+    }
+
+    /// [`to_bytes`](Self::to_bytes) followed by an opaque `metadata` section, e.g. the
+    /// caller's schema version or build time. Read it back with
+    /// [`from_bytes_with_metadata`](Self::from_bytes_with_metadata).
+    #[cfg(feature = "serde")]
+    pub fn to_bytes_with_metadata(&self, metadata: &[u8]) -> Result<Vec<u8>, MphError> {
+        let mut out = self.to_bytes()?;
+        out.reserve(8 + metadata.len());
+        write_metadata(&mut out, metadata)?;
+        Ok(out)
+    }
+
+    /// [`write_to`](Self::write_to), then `metadata` as an 8-byte little-endian length
+    /// followed by the bytes. The crate never interprets them. Plain `read_from` and
+    /// `from_bytes` still accept the result and skip the section.
+    #[cfg(feature = "serde")]
+    pub fn write_to_with_metadata(
+        &self,
+        mut w: impl std::io::Write,
+        metadata: &[u8],
+    ) -> Result<(), MphError> {
+        self.write_to(&mut w)?;
+        write_metadata(w, metadata)
+    }
+
+    /// Counterpart of [`to_bytes_with_metadata`](Self::to_bytes_with_metadata). A blob
+    /// without a metadata section (from [`to_bytes`](Self::to_bytes)) yields an empty one.
+    #[cfg(feature = "serde")]
+    pub fn from_bytes_with_metadata(bytes: &[u8]) -> Result<(Self, Vec<u8>), MphError> {
+        let mut rest = bytes;
+        let mph = Self::read_from(&mut rest)?;
+        if rest.is_empty() {
+            return Ok((mph, Vec::new()));
+        }
+        let metadata = read_metadata(rest)?;
+        Ok((mph, metadata))
+    }
+
+    /// Counterpart of [`write_to_with_metadata`](Self::write_to_with_metadata). Unlike
+    /// the slice version, the metadata section is required: a stream cannot tell a
+    /// missing section from whatever the caller wrote after the `Mphf`.
+    #[cfg(feature = "serde")]
+    pub fn read_from_with_metadata(mut r: impl std::io::Read) -> Result<(Self, Vec<u8>), MphError> {
+        let mph = Self::read_from(&mut r)?;
+        let metadata = read_metadata(r)?;
+        Ok((mph, metadata))
+    }
+}
+
+#[cfg(feature = "serde")]
+fn write_metadata(mut w: impl std::io::Write, metadata: &[u8]) -> Result<(), MphError> {
+    let len = (metadata.len() as u64).to_le_bytes();
+    w.write_all(&len)
+        .and_then(|()| w.write_all(metadata))
+        .map_err(|e| MphError::Serde(e.into()))
+}
+
+/// Length-prefixed section written by [`write_metadata`]. Reads through `take` so a
+/// corrupt length fails at the end of the input instead of allocating it up front.
+#[cfg(feature = "serde")]
+fn read_metadata(mut r: impl std::io::Read) -> Result<Vec<u8>, MphError> {
+    let io = |e: std::io::Error| MphError::Serde(e.into());
+    let mut len = [0u8; 8];
+    r.read_exact(&mut len).map_err(io)?;
+    let len = u64::from_le_bytes(len);
+    let mut metadata = Vec::new();
+    std::io::Read::read_to_end(&mut r.take(len), &mut metadata).map_err(io)?;
+    if metadata.len() as u64 != len {
+        return Err(io(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(metadata)
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<&[u8]> for Mphf {
+    type Error = MphError;
+
+    /// Same as [`Mphf::from_bytes`].
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Mphf::from_bytes(bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Mphf> for Vec<u8> {
+    type Error = MphError;
+
+    /// Same as [`Mphf::to_bytes`].
+    fn try_from(mph: Mphf) -> Result<Self, Self::Error> {
+        mph.to_bytes()
+    }
+}
+
+/// Peelability threshold of random 3-uniform hypergraphs (`1 / 0.8185`): below this
+/// `gamma`, builds over large key sets essentially never succeed.
+pub const MIN_GAMMA_RANK3: f64 = 1.222;
+
+/// Builder configuration.
+/// For huge datasets (e.g., 100M keys) set `gamma ≈ 1.27` to reduce rehash retries.
+///
+/// With the `serde` feature it (de)serializes as a plain struct; fields missing from
+/// the input take their [`Default`] values, so a manifest only lists what it changes.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Debug, Clone)]
+pub struct BuildConfig {
+    /// Vertex ratio m/n; BDZ classic ~1.23. For 100M keys a good value is 1.27.
+    pub gamma: f64,
+    /// Maximum rehash attempts if the graph is not peelable: the build tries
+    /// `rehash_limit + 1` salts, so `0` means a single attempt.
+    /// [`UNBOUNDED_REHASH`](Self::UNBOUNDED_REHASH) keeps trying until one peels.
+    pub rehash_limit: u32,
+    /// Base salt. Effective salts are derived deterministically. `0` is an ordinary
+    /// seed, not "unseeded": both schedules mix it into a nonzero salt.
+    pub salt: u64,
+    /// Store a 1-byte fingerprint per key so [`Mphf::index_if_member`] rejects
+    /// ~255/256 of non-members. Costs 1 byte/key and one extra hash per key at build.
+    pub fingerprint: bool,
+    /// Verify key uniqueness in [`Builder::build_indexed`] (`build` always does).
+    pub check_unique_indexed: bool,
+    /// After a successful round, look up every build key once more and fail with
+    /// [`MphError::NotBijective`] if two land on the same index. Costs one lookup per
+    /// key plus `8n` bytes; catches assignment regressions and hash collisions.
+    pub post_build_verify: bool,
+    /// When false, skip minimization: `index` maps into `[0, m)` (perfect but not
+    /// minimal) using a 2-bit choice among the key's three vertices, and arrays
+    /// indexed by it must be sized [`Mphf::range`] (= `m`). Peeling is unchanged, so
+    /// this does not make builds more reliable; it only drops the `% n` ranking.
+    /// Sets below 32 keys are built minimal regardless.
+    pub require_minimal: bool,
+    /// Round `m` up to the next prime. The stored `m` is the rounded one, so lookups
+    /// are unaffected; it only changes the `% m` that spreads vertices. Measured
+    /// without effect so far: sequential, strided and shared-prefix keys at
+    /// `gamma = 1.222` with `m = 2^16` averaged the same rehash rounds either way
+    /// (within noise over 200 salts), as splitmix64 leaves no bias for `% m` to expose.
+    pub prime_m: bool,
+    /// Peel without the CSR adjacency: each vertex tracks the XOR of its incident
+    /// edges instead, which drops about `13 * n + 12 * m` bytes (see the [`Builder`]
+    /// memory table). On 2M keys at the default `gamma` the build peaked at 40 instead
+    /// of 69 bytes per key and ran about 30% faster, with the same first-round success
+    /// rate near [`MIN_GAMMA_RANK3`]. The peel order, and so the indices, differ from
+    /// the default path.
+    pub low_memory_peel: bool,
+    /// How each rehash round's salt is derived from `salt`. Measured without effect
+    /// so far: at `gamma = 1.222`, 400 sets each of 5000 sequential `u64` keys and of
+    /// shared-prefix paths averaged 1.9–2.1 rounds under either schedule (within noise),
+    /// as wyhash already decorrelates nearby seeds.
+    pub salt_schedule: SaltSchedule,
+    /// First rehash round to try: a build tries rounds `salt_round_start` through
+    /// `salt_round_start + rehash_limit`. Rounds are numbered independently of where
+    /// a build starts, so a non-zero start skips exactly the salts of the rounds before
+    /// it, e.g. those an earlier build already failed (see [`Builder::continue_build`]).
+    pub salt_round_start: u32,
+}
+
+/// Derivation of the per-round effective salts from [`BuildConfig::salt`]. The
+/// winning salt is stored in the `Mphf`, so lookups do not depend on it; changing
+/// it only changes which salts a build tries, and so the resulting indices.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SaltSchedule {
+    /// FNV-style mix of the base salt and the round number.
+    #[default]
+    Fnv,
+    /// `splitmix64(salt + round * φ)`: the splitmix64 stream seeded with `salt`,
+    /// whose consecutive outputs are statistically independent.
+    SplitMix,
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        Self {
+            gamma: 1.27,
+            rehash_limit: 16,
+            salt: 0x0C0F_FEE0_0D15_EA5E,
+            fingerprint: false,
+            check_unique_indexed: false,
+            post_build_verify: false,
+            require_minimal: true,
+            prime_m: false,
+            low_memory_peel: false,
+            salt_schedule: SaltSchedule::Fnv,
+            salt_round_start: 0,
+        }
+    }
+}
+
+impl BuildConfig {
+    /// `rehash_limit` for builds that must succeed and may take their time: every
+    /// one of the 2^32 round salts is tried before giving up, which in practice means
+    /// retrying until the graph peels. There is no time bound, so keep `gamma` at a
+    /// value that peels within a few rounds. An input that never peels (duplicates
+    /// passed unchecked to [`Builder::build_indexed`]) spins for a very long time
+    /// instead of failing.
+    pub const UNBOUNDED_REHASH: u32 = u32::MAX;
+
+    /// Defaults with `gamma` picked from the key count. The peeling threshold gets
+    /// sharper as `n` grows, so large sets can sit closer to [`MIN_GAMMA_RANK3`];
+    /// small sets keep more headroom, which costs little memory in absolute terms.
+    pub fn recommended(n: usize) -> BuildConfig {
+        let gamma = match n {
+            0..=100_000 => 1.30,
+            100_001..=10_000_000 => 1.25,
+            _ => 1.23,
+        };
+        BuildConfig {
+            gamma,
+            ..Default::default()
+        }
+    }
+
+    /// Preset for quick builds: `gamma = 1.30` makes most graphs peel on the first
+    /// salt, so `rehash_limit = 4` is plenty. Costs about 2% more `g` than
+    /// [`balanced`](Self::balanced).
+    pub fn fast() -> BuildConfig {
+        BuildConfig {
+            gamma: 1.30,
+            rehash_limit: 4,
+            ..Default::default()
+        }
+    }
+
+    /// Preset for the smallest table: `gamma = 1.23`, just above [`MIN_GAMMA_RANK3`],
+    /// with `rehash_limit = 64` to absorb the extra failed rounds. Reliable for large
+    /// key sets; below ~100k keys the threshold is fuzzy and builds take more rounds,
+    /// so [`recommended`](Self::recommended) keeps more headroom there.
+    pub fn compact() -> BuildConfig {
+        BuildConfig {
+            gamma: 1.23,
+            rehash_limit: 64,
+            ..Default::default()
+        }
+    }
+
+    /// The [`Default`] configuration (`gamma = 1.27`, `rehash_limit = 16`): rarely
+    /// needs a rehash at any scale, at about 3% more `g` than [`compact`](Self::compact).
+    pub fn balanced() -> BuildConfig {
+        BuildConfig::default()
+    }
+
+    /// Effective salt of rehash round `round`, per [`salt_schedule`](Self::salt_schedule).
+    #[inline]
+    pub(crate) fn round_salt(&self, round: u32) -> u64 {
+        match self.salt_schedule {
+            SaltSchedule::Fnv => mix_salt(self.salt, round),
+            SaltSchedule::SplitMix => splitmix64(
+                self.salt
+                    .wrapping_add((round as u64).wrapping_mul(GOLDEN_GAMMA)),
+            ),
+        }
+    }
+
+    /// Salt of the first round a build tries.
+    #[inline]
+    pub(crate) fn first_salt(&self) -> u64 {
+        self.round_salt(self.salt_round_start)
+    }
+
+    /// Reject configurations that cannot build: non-finite `gamma` (NaN would
+    /// otherwise collapse to `m = 1`) or `gamma` below [`MIN_GAMMA_RANK3`].
+    pub fn validate(&self) -> Result<(), MphError> {
+        if !self.gamma.is_finite() {
+            return Err(MphError::InvalidConfig(format!(
+                "gamma must be finite, got {}",
+                self.gamma
+            )));
+        }
+        if self.gamma < MIN_GAMMA_RANK3 {
+            return Err(MphError::InvalidConfig(format!(
+                "gamma {} is below the peelability threshold {MIN_GAMMA_RANK3}",
+                self.gamma
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Statistics collected by [`Builder::build_with_stats`].
+#[derive(Debug, Clone, Default)]
+pub struct BuildStats {
+    /// Rehash round (in `salt_round_start..=salt_round_start + rehash_limit`) whose
+    /// salt produced a peelable graph. Frequent non-zero rounds suggest `gamma` is too
+    /// tight.
+    pub round: u32,
+    /// Vertex degree profile of the first attempt's hypergraph (all zero below 32 keys).
+    /// A well-spread hash gives roughly Poisson degrees with mean and variance `3n/m`.
+    pub degrees: DegreeStats,
+    /// Highest vertex degree in the hypergraph of the attempt that succeeded (0 below
+    /// 32 keys). A spike across builds of similar sets points at key-distribution drift
+    /// before it shows up as failed peels.
+    pub max_degree: u32,
+    /// The configured `gamma` was raised to 1.5 for this set of 32–255 keys, where a
+    /// smaller one fails every rehash round too often; [`Mphf::achieved_gamma`] gives
+    /// the ratio used.
+    pub padded: bool,
+}
+
+/// Where a build that ran out of rehash rounds stopped, for
+/// [`Builder::continue_build`].
+#[derive(Debug, Clone)]
+pub struct FailedBuild {
+    /// Configuration of the failed build.
+    pub cfg: BuildConfig,
+    /// Last salt round it tried.
+    pub last_round: u32,
+}
+
+impl FailedBuild {
+    /// Resume point of a build run with `cfg` that returned `err`; `None` unless `err`
+    /// is [`MphError::Unresolvable`].
+    pub fn from_error(cfg: BuildConfig, err: &MphError) -> Option<Self> {
+        match *err {
+            MphError::Unresolvable { last_round, .. } => Some(Self { cfg, last_round }),
+            _ => None,
+        }
+    }
+}
+
+/// Vertex degree distribution of one BDZ hypergraph, a cheap hash-quality signal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DegreeStats {
+    pub max: u32,
+    pub mean: f64,
+    pub variance: f64,
+    /// Variance above `1.5 × mean` or a maximum far in the Poisson tail: the key set
+    /// hashes poorly (adversarial or pathological) and may need a different salt or a
+    /// keyed hash.
+    pub skewed: bool,
+}
+
+impl DegreeStats {
+    fn from_degrees(deg: &[u32]) -> Self {
+        if deg.is_empty() {
+            return Self::default();
+        }
+        let m = deg.len() as f64;
+        let mean = deg.iter().map(|&d| d as f64).sum::<f64>() / m;
+        let variance = deg.iter().map(|&d| (d as f64 - mean).powi(2)).sum::<f64>() / m;
+        let max = deg.iter().copied().max().unwrap_or(0);
+        let skewed = variance > 1.5 * mean || max as f64 > mean + 8.0 * mean.sqrt() + 8.0;
+        Self {
+            max,
+            mean,
+            variance,
+            skewed,
+        }
+    }
+}
+
+/// Result of [`Mphf::validate_against`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Keys mapped before the check finished.
+    pub checked: usize,
+    /// Every index in `[0, range)` was hit exactly once: the keys are a bijection onto
+    /// the range. Never true for non-minimal builds of 32+ keys, whose range `m` exceeds `n`.
+    pub covers_range: bool,
+    /// Smallest and largest index observed (`None` for no keys).
+    pub min_index: Option<u64>,
+    pub max_index: Option<u64>,
+    /// The first pair of keys sharing an index, if any.
+    pub first_collision: Option<IndexCollision>,
+}
+
+impl ValidationReport {
+    /// No collision and the range is exactly covered.
+    pub fn is_ok(&self) -> bool {
+        self.covers_range && self.first_collision.is_none()
+    }
+}
+
+/// Two keys mapped to the same `index`; `first` and `second` are their positions in
+/// the checked keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexCollision {
+    pub index: u64,
+    pub first: usize,
+    pub second: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum MphError {
+    #[error("duplicate key detected during build")]
+    DuplicateKey,
+    /// Returned where an empty key set is an ordinary input rather than a caller bug,
+    /// e.g. freezing an empty map or a filter keeping no key; a plain
+    /// [`Builder::build`] over no keys panics.
+    #[error("empty key set is not supported")]
+    EmptyKeySet,
+    /// `best_peeled` close to `n` hints that a slightly larger `gamma` would succeed.
+    /// `last_round` is the last salt round tried; [`FailedBuild::from_error`] picks
+    /// it up to resume after it.
+    #[error(
+        "graph was not peelable after {rounds_tried} rehash attempts \
+         (best attempt peeled {best_peeled}/{n} edges)"
+    )]
+    Unresolvable {
+        rounds_tried: u32,
+        last_round: u32,
+        best_peeled: usize,
+        n: usize,
+    },
+    #[error("invalid build configuration: {0}")]
+    InvalidConfig(String),
+    /// Also returned for more than [`MAX_KEYS`] keys, whose `g` sums would overflow.
+    #[error(
+        "too many keys (n > u32::MAX / 3) or vertex count \
+         m = ceil(gamma * n) exceeds u32::MAX"
+    )]
+    TooManyVertices,
+    #[error("precomputed vertices are inconsistent (empty, length mismatch or vertex >= m)")]
+    InvalidVertices,
+    #[error("key offsets decrease or run past the end of the blob")]
+    InvalidOffsets,
+    /// Raised by [`BuildConfig::post_build_verify`] and [`Builder::build_checked`];
+    /// `first` and `second` are the positions of the two keys (in build order) that
+    /// share `index`.
+    #[error("keys #{first} and #{second} both map to index {index}")]
+    NotBijective {
+        index: u64,
+        first: usize,
+        second: usize,
+    },
+    /// Only reachable where `usize` is 32 bits (e.g. wasm32).
+    #[error("build over {n} keys and {m} vertices exceeds this target's address space")]
+    AddressSpace { n: usize, m: u32 },
+    #[cfg(feature = "binary")]
+    #[error("malformed binary MPH: {0}")]
+    Binary(&'static str),
+    #[cfg(feature = "serde")]
+    #[error("serialization error: {0}")]
+    Serde(#[from] Box<bincode::ErrorKind>),
+    /// The blob was written with a [`HASH_VERSION`] this build cannot evaluate.
+    #[cfg(any(feature = "serde", feature = "binary"))]
+    #[error("MPH blob uses hash version {0}, which this build does not support")]
+    UnsupportedHashVersion(u8),
+    /// The blob decoded, but its fields are inconsistent (truncated or tampered with).
+    #[cfg(feature = "serde")]
+    #[error("corrupt MPH blob: {0}")]
+    Corrupt(&'static str),
+}
+
+// `MphError` must stay usable as `anyhow::Error` / `Box<dyn Error + Send + Sync>`
+// and across `.await` points; a new variant holding e.g. an `Rc` fails here.
+const _: () = {
+    const fn assert_error<T: std::error::Error + Send + Sync + 'static>() {}
+    assert_error::<MphError>();
+};
+
+/// BDZ builder.
+///
+/// # Memory
+///
+/// Allocations come from the global allocator. Peak usage of one attempt, for `n`
+/// keys and `m = ceil(gamma * n)` vertices (scratch is reused across rounds):
+///
+/// | buffer            | bytes           |
+/// | ----------------- | --------------- |
+/// | `v0`, `v1`, `v2`  | `12 * n`        |
+/// | `edges` (CSR)     | `12 * n`        |
+/// | `peel_order`      | `8 * n`         |
+/// | `removed`         | `n`             |
+/// | `deg`             | `4 * m`         |
+/// | `q` (peel queue)  | up to `2 * m`   |
+/// | `off`, `cur`      | `8 * (m + 1)` each |
+/// | `g` (output)      | `4 * m`         |
+///
+/// The peel queue is a ring holding only the degree-1 vertices not yet visited, which
+/// peaked at about `m / 4` of them on 2M keys (capacity rounds up to a power of two).
+/// That is about `33 * n + 26 * m` bytes (≈ `66 * n` at `gamma = 1.27`).
+/// [`BuildConfig::low_memory_peel`] replaces `edges`, `off`, `cur` and `removed` by a
+/// `4 * m` XOR array, for `20 * n + 14 * m` (≈ `38 * n`). On top of that each key is
+/// copied once (the key bytes plus a 24-byte `Vec` header), and dedup keeps an
+/// 8-byte hash and an 8-byte index per key plus hash-table slack.
+/// The hash doubles as the round-0 vertex hash, so keys are hashed once unless the
+/// build needs a rehash.
+///
+/// [`build_into`](Self::build_into) keeps all of these buffers in the builder
+/// between calls (keys go to a single blob instead), which suits building many
+/// small MPHs in a row.
+pub struct Builder {
+    cfg: BuildConfig,
+    scratch: Scratch,
+    keys: KeyArena,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            cfg: BuildConfig::default(),
+            scratch: Scratch::default(),
+            keys: KeyArena::default(),
+        }
+    }
+    pub fn with_config(mut self, cfg: BuildConfig) -> Self {
+        self.cfg = cfg;
+        self
+    }
+    /// Shortcut for setting `BuildConfig::salt` alone.
+    pub fn with_salt(mut self, salt: u64) -> Self {
+        self.cfg.salt = salt;
+        self
+    }
+    /// Shortcut for setting `BuildConfig::gamma` alone; validated at build time.
+    pub fn with_gamma(mut self, gamma: f64) -> Self {
+        self.cfg.gamma = gamma;
+        self
+    }
+    /// Shortcut for setting `BuildConfig::rehash_limit` alone.
+    pub fn with_rehash_limit(mut self, rehash_limit: u32) -> Self {
+        self.cfg.rehash_limit = rehash_limit;
+        self
+    }
+
+    /// Build MPH from **unique** keys.
+    ///
+    /// Keys are arbitrary byte strings: the empty key `b""` is valid and embedded or
+    /// trailing NULs are significant (`b"a"` and `b"a\0"` are distinct keys).
+    /// `DuplicateKey` is returned on exact byte equality only.
+    pub fn build<K, I>(self, keys: I) -> Result<Mphf, MphError>
+    where
+        K: Borrow<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        self.build_with_stats(keys).map(|(mph, _)| mph)
+    }
+
+    /// Retry a build that failed with [`MphError::Unresolvable`] for `extra_rounds`
+    /// more rounds, starting after the last one it tried (via
+    /// [`BuildConfig::salt_round_start`]), so no failed salt is tried twice. The
+    /// builder's own config is replaced by `prior.cfg`. Only the peeling rounds are
+    /// skipped: collecting, deduplicating and hashing the keys repeats, as the
+    /// vertices of earlier rounds are of no use to new salts. To escalate again after
+    /// another failure, pass it to [`FailedBuild::from_error`] with `prior.cfg`.
+    ///
+    /// `extra_rounds` must be at least 1, and `prior.last_round` below `u32::MAX` (no
+    /// round is left after it); both fail with `InvalidConfig` otherwise.
+    pub fn continue_build<K, I>(
+        mut self,
+        prior: FailedBuild,
+        extra_rounds: u32,
+        keys: I,
+    ) -> Result<Mphf, MphError>
+    where
+        K: Borrow<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        if extra_rounds == 0 {
+            return Err(MphError::InvalidConfig(
+                "continue_build needs at least one extra round".into(),
+            ));
+        }
+        let Some(salt_round_start) = prior.last_round.checked_add(1) else {
+            return Err(MphError::InvalidConfig(
+                "continue_build: every salt round up to u32::MAX was tried".into(),
+            ));
+        };
+        self.cfg = BuildConfig {
+            salt_round_start,
+            rehash_limit: extra_rounds - 1,
+            ..prior.cfg
+        };
+        self.build(keys)
+    }
+
+    /// [`build`](Self::build), then look up every key once more and fail with
+    /// [`MphError::NotBijective`] if two share an index, i.e. with
+    /// [`BuildConfig::post_build_verify`] forced on. A successful peel always yields a
+    /// bijection, so that error means a bug in this crate, not in the input.
+    pub fn build_checked<K, I>(mut self, keys: I) -> Result<Mphf, MphError>
+    where
+        K: Borrow<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        self.cfg.post_build_verify = true;
+        self.build(keys)
+    }
+
+    /// Same as [`build`](Self::build), also returning [`BuildStats`].
+    pub fn build_with_stats<K, I>(self, keys: I) -> Result<(Mphf, BuildStats), MphError>
+    where
+        K: Borrow<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        let uniq = collect_unique(keys, self.cfg.first_salt())?;
+        self.build_unique(&uniq.keys, Some(&uniq.bases))
+    }
+
+    /// Lenient [`build`](Self::build): duplicates are reported instead of rejected.
+    ///
+    /// The build uses the first occurrence of every key; the second element lists each
+    /// later occurrence as `(first_position, duplicate_position)` in input order, so
+    /// duplicates can be logged and fixed without aborting the pipeline. Keys are
+    /// borrowed from the collected input, not copied. An empty input yields
+    /// `EmptyKeySet`.
+    pub fn build_report_duplicates<K, I>(
+        self,
+        keys: I,
+    ) -> (Result<Mphf, MphError>, Vec<(usize, usize)>)
+    where
+        K: Borrow<[u8]> + Sync,
+        I: IntoIterator<Item = K>,
+    {
+        let all: Vec<K> = keys.into_iter().collect();
+        let mut first = HashMap::<&[u8], usize>::with_capacity(all.len());
+        let mut uniq = Vec::with_capacity(all.len());
+        let mut dups = Vec::new();
+        for (pos, k) in all.iter().enumerate() {
+            match first.entry(k.borrow()) {
+                Entry::Occupied(e) => dups.push((*e.get(), pos)),
+                Entry::Vacant(e) => {
+                    e.insert(pos);
+                    uniq.push(pos);
+                }
+            }
+        }
+        drop(first);
+        if uniq.is_empty() {
+            return (Err(MphError::EmptyKeySet), dups);
+        }
+        let mph = self
+            .build_keys(uniq.len(), |i| all[uniq[i]].borrow())
+            .map(|(mph, _)| mph);
+        (mph, dups)
+    }
+
+    /// Build MPH from **unique** keys, reusing the buffers of previous `build_into`
+    /// calls on this builder instead of allocating afresh.
+    ///
+    /// `out`'s previous `Mphf`, if any, lends its `g` allocation and is replaced by the
+    /// new one; on error `out` is left `None`. Every buffer is cleared before use, so
+    /// nothing from an earlier build (keys, graph, salts) can leak into this one.
+    pub fn build_into<K, I>(&mut self, keys: I, out: &mut Option<Mphf>) -> Result<(), MphError>
+    where
+        K: Borrow<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        self.reset();
+        if let Some(prev) = out.take() {
+            self.scratch.graph.g = prev.g;
+        }
+        for k in keys {
+            self.keys.push(k.borrow())?;
+        }
+        let Self { cfg, scratch, keys } = self;
+        let (mph, _) = build_keys_in(cfg, keys.len(), |i| keys.get(i), None, scratch)?;
+        *out = Some(mph);
+        Ok(())
+    }
+
+    /// Clear the buffers retained by [`build_into`](Self::build_into), keeping their
+    /// capacity. `build_into` does this itself; call it to drop the previous keys
+    /// from memory early.
+    pub fn reset(&mut self) {
+        self.scratch.clear();
+        self.keys.clear();
+    }
+
+    /// Build MPH from owned **unique** keys, moving them instead of copying.
+    ///
+    /// Uniqueness is checked as in [`build`](Self::build), whose dedup hashes double
+    /// as the first round's, so no key is cloned or hashed an extra time.
+    /// Use [`build`](Self::build) for borrowed inputs.
+    pub fn build_owned<I>(self, keys: I) -> Result<Mphf, MphError>
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+    {
+        let uniq = unique_owned(keys.into_iter().collect(), self.cfg.first_salt())?;
+        self.build_unique(&uniq.keys, Some(&uniq.bases))
+            .map(|(mph, _)| mph)
+    }
+
+    /// Build MPH from borrowed **unique** strings without copying any of them.
+    ///
+    /// Keys are collected as `&str` and deduplicated through a `HashSet<&str>`, so
+    /// peak memory is two pointers per key instead of an owned copy of each key. The
+    /// strings must outlive the call (the borrow checker enforces this); the resulting
+    /// `Mphf` does not reference them. Use [`build_owned`](Self::build_owned) when
+    /// keys cannot be borrowed.
+    pub fn build_strs<'a, I>(self, keys: I) -> Result<Mphf, MphError>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let keys: Vec<&str> = keys.into_iter().collect();
+        let mut seen = HashSet::<&str>::with_capacity(keys.len());
+        if !keys.iter().all(|k| seen.insert(k)) {
+            return Err(MphError::DuplicateKey);
+        }
+        drop(seen);
+        self.build_keys(keys.len(), |i| keys[i].as_bytes())
+            .map(|(mph, _)| mph)
+    }
+
+    /// Build MPH from fixed-size **unique** keys, the counterpart of [`Mphf::index_fixed`].
+    /// Keys are borrowed, not copied; the resulting MPH also answers `index(&key[..])`.
+    pub fn build_fixed<const N: usize>(self, keys: &[[u8; N]]) -> Result<Mphf, MphError> {
+        let mut seen = HashSet::<&[u8; N]>::with_capacity(keys.len());
+        if !keys.iter().all(|k| seen.insert(k)) {
+            return Err(MphError::DuplicateKey);
+        }
+        drop(seen);
+        self.build_keys(keys.len(), |i| &keys[i][..])
+            .map(|(mph, _)| mph)
+    }
+
+    /// Pick the smallest `gamma` that reliably peels `sample`, returning the builder's
+    /// config with that `gamma`.
+    ///
+    /// Binary-searches `gamma` between [`MIN_GAMMA_RANK3`] and the configured value;
+    /// a candidate passes when the sample peels under each of a few salts (without the
+    /// small-set padding of `m`). This is a heuristic: the outcome depends on the
+    /// sample, and small samples peel less predictably than the full set, so keep some
+    /// `rehash_limit` headroom. Sample keys must be unique. If even the configured
+    /// `gamma` fails, the config is returned unchanged.
+    ///
+    /// Builds of fewer than 256 keys still raise the returned `gamma` to 1.5 (see
+    /// [`BuildStats::padded`]), so calibrating only pays off for larger sets.
+    pub fn calibrate(self, sample: &[&[u8]]) -> BuildConfig {
+        const TRIALS: u32 = 4;
+        const STEPS: u32 = 10;
+
+        let n = sample.len();
+        if n <= 2 {
+            return self.cfg;
+        }
+        let get = |i: usize| sample[i];
+        let mut scratch = Scratch::default();
+        let mut peels = |gamma: f64| {
+            let m = (gamma * n as f64).ceil();
+            m <= u32::MAX as f64
+                && (0..TRIALS).all(|t| {
+                    let salt = self.cfg.round_salt(t);
+                    try_build_bdz(n, &get, None, salt, m as u32, true, &mut scratch).is_ok()
+                })
+        };
+
+        let (mut lo, mut hi) = (MIN_GAMMA_RANK3, self.cfg.gamma.max(MIN_GAMMA_RANK3));
+        if !peels(hi) {
+            return self.cfg;
+        }
+        for _ in 0..STEPS {
+            let mid = (lo + hi) / 2.0;
+            if peels(mid) {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        BuildConfig {
+            gamma: hi,
+            ..self.cfg
+        }
+    }
+
+    /// Build MPH plus its inverse: `inverse[i]` is the key mapped to index `i`.
+    /// It has [`Mphf::range`] entries: for non-minimal builds, indices no key maps
+    /// to hold an empty `Vec`.
+    ///
+    /// The inverse owns a copy of every key, so memory grows by the total key
+    /// bytes plus a `Vec` header (24 bytes) per key — typically far more than the
+    /// MPH itself. Opt in only when index → key resolution is needed.
+    pub fn build_with_inverse<K, I>(self, keys: I) -> Result<(Mphf, Vec<Vec<u8>>), MphError>
+    where
+        K: Borrow<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        let mut uniq = collect_unique(keys, self.cfg.first_salt())?;
+        let (mph, _) = self.build_unique(&uniq.keys, Some(&uniq.bases))?;
+        // Move (not clone) the collected keys into index order.
+        let mut inverse = vec![Vec::new(); mph.range() as usize];
+        for k in &mut uniq.keys {
+            let i = mph.index(k) as usize;
+            inverse[i] = std::mem::take(k);
+        }
+        Ok((mph, inverse))
+    }
+
+    /// Build over `n` keys supplied by `get(i)` for `i in 0..n`, without copying them
+    /// (e.g. keys living in an mmap or an arena).
+    ///
+    /// `get` must be pure: the same `i` must always return the same bytes. Keys are
+    /// read again on every rehash round and for fingerprints, possibly from several
+    /// threads at once. Keys must be unique; set `BuildConfig::check_unique_indexed`
+    /// to verify that (a set of borrowed slices, no copies). Unchecked duplicates
+    /// surface as `Unresolvable` once every round has failed.
+    pub fn build_indexed<'k, F>(self, n: usize, get: F) -> Result<Mphf, MphError>
+    where
+        F: Fn(usize) -> &'k [u8] + Sync,
+    {
+        if self.cfg.check_unique_indexed {
+            let mut seen = HashSet::<&[u8]>::with_capacity(n);
+            for i in 0..n {
+                if !seen.insert(get(i)) {
+                    return Err(MphError::DuplicateKey);
+                }
+            }
+        }
+        self.build_keys(n, get).map(|(mph, _)| mph)
+    }
+
+    /// Build over keys stored back to back in `blob`: key `i` is
+    /// `blob[offsets[i]..offsets[i + 1]]`, so `n = offsets.len() - 1` (the columnar
+    /// layout of Arrow-style string arrays). Keys are hashed straight from `blob`;
+    /// nothing is copied or allocated per key.
+    ///
+    /// `offsets` must be non-decreasing and end within `blob`, else `InvalidOffsets`;
+    /// fewer than two offsets (no key) fail with `EmptyKeySet`. Uniqueness is handled
+    /// as in [`build_indexed`](Self::build_indexed).
+    pub fn build_from_blob(self, blob: &[u8], offsets: &[u32]) -> Result<Mphf, MphError> {
+        let in_order = offsets.windows(2).all(|w| w[0] <= w[1]);
+        if !in_order || offsets.last().is_some_and(|&end| end as usize > blob.len()) {
+            return Err(MphError::InvalidOffsets);
+        }
+        if offsets.len() < 2 {
+            return Err(MphError::EmptyKeySet);
+        }
+        let n = offsets.len() - 1;
+        self.build_indexed(n, |i| &blob[offsets[i] as usize..offsets[i + 1] as usize])
+    }
+
+    /// Shard `keys` by [`partition_of`] into `partitions` parts, build every part on
+    /// its own thread (rayon, with the "parallel" feature; one after the other
+    /// otherwise) and combine them with [`Mphf::concat`]. `index` of the result is a
+    /// bijection onto `[0, keys.len())` for minimal configs.
+    ///
+    /// `partitions = 0` picks [`std::thread::available_parallelism`]. Either way the
+    /// count used is `min(partitions, keys.len() / 1024).max(1)`, so parts average at
+    /// least 1024 keys: a larger explicit value is lowered, not rejected, and
+    /// [`ConcatMphf::parts`] reports the count actually used. Empty `keys` fail with
+    /// `EmptyKeySet`; a part that still ends up empty (only with adversarial keys)
+    /// fails with `InvalidConfig`. Duplicates land in the same part and fail its build
+    /// with `DuplicateKey`.
+    pub fn build_parallel<K>(self, keys: &[K], partitions: usize) -> Result<ConcatMphf, MphError>
+    where
+        K: AsRef<[u8]> + Sync,
+    {
+        const MIN_PART_KEYS: usize = 1024;
+        if keys.is_empty() {
+            return Err(MphError::EmptyKeySet);
+        }
+        let requested = match partitions {
+            0 => std::thread::available_parallelism().map_or(1, |p| p.get()),
+            p => p,
+        };
+        let parts = requested.min(keys.len() / MIN_PART_KEYS).max(1);
+
+        let mut shards: Vec<Vec<&[u8]>> = vec![Vec::new(); parts];
+        for k in keys {
+            let k = k.as_ref();
+            shards[partition_of(k, parts)].push(k);
+        }
+        if let Some(p) = shards.iter().position(Vec::is_empty) {
+            return Err(MphError::InvalidConfig(format!(
+                "partition {p} of {parts} received no keys"
+            )));
+        }
+
+        let cfg = &self.cfg;
+        let build = |shard: &Vec<&[u8]>| {
+            Builder::new()
+                .with_config(cfg.clone())
+                .build(shard.iter().copied())
+        };
+        #[cfg(feature = "parallel")]
+        let built: Result<Vec<Mphf>, MphError> = {
+            use rayon::prelude::*;
+            shards.par_iter().map(build).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let built: Result<Vec<Mphf>, MphError> = shards.iter().map(build).collect();
+        ConcatMphf::from_parts(built?)
+    }
+
+    /// Rehash loop over already deduplicated keys. `bases`, if known, are the
+    /// round-0 base hashes from [`collect_unique`].
+    fn build_unique(
+        &self,
+        uniq: &[Vec<u8>],
+        bases: Option<&[u64]>,
+    ) -> Result<(Mphf, BuildStats), MphError> {
+        let get = |i: usize| uniq[i].as_slice();
+        build_keys_in(&self.cfg, uniq.len(), get, bases, &mut Scratch::default())
+    }
+
+    /// Rehash loop over `n` unique keys read through `get`.
+    pub(crate) fn build_keys<'k, F>(&self, n: usize, get: F) -> Result<(Mphf, BuildStats), MphError>
+    where
+        F: Fn(usize) -> &'k [u8] + Sync,
+    {
+        build_keys_in(&self.cfg, n, get, None, &mut Scratch::default())
+    }
+
+    /// Build MPH from vertices precomputed with [`prehash`].
+    ///
+    /// `m` and `salt` must be the exact values passed to `prehash`. No rehash is
+    /// possible here (the salt is baked into the vertices), so a non-peelable graph
+    /// yields `Unresolvable`. Duplicate keys cannot be detected either: they produce
+    /// identical edges, which never peel, and also surface as `Unresolvable`.
+    /// Empty or mismatched vertex arrays, vertices outside `[0, m)` and `m <= n` are
+    /// `InvalidVertices`; `m == n` is the shape of a small set's sorted hash table.
+    pub fn build_from_vertices(
+        self,
+        v0: &[u32],
+        v1: &[u32],
+        v2: &[u32],
+        m: u64,
+        salt: u64,
+    ) -> Result<Mphf, MphError> {
+        let n = v0.len();
+        if n == 0 || v1.len() != n || v2.len() != n || m <= n as u64 || m > u32::MAX as u64 {
+            return Err(MphError::InvalidVertices);
+        }
+        let in_range = |v: &[u32]| v.iter().all(|&x| (x as u64) < m);
+        if !(in_range(v0) && in_range(v1) && in_range(v2)) {
+            return Err(MphError::InvalidVertices);
+        }
+        if n > MAX_KEYS {
+            return Err(MphError::TooManyVertices);
+        }
+        check_address_space(n, m as u32)?;
+        build_from_edges(
+            [v0, v1, v2],
+            n,
+            m as u32,
+            salt,
+            self.cfg.require_minimal,
+            &mut GraphScratch {
+                low_memory: self.cfg.low_memory_peel,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// [`Builder::build_keys`] with caller-provided buffers.
+///
+/// `bases[i]`, if given, must be `wyhash1(get(i), cfg.first_salt())`; the first
+/// round then derives its vertices from those instead of hashing the keys again.
+fn build_keys_in<'k, F>(
+    cfg: &BuildConfig,
+    n: usize,
+    get: F,
+    bases: Option<&[u64]>,
+    scratch: &mut Scratch,
+) -> Result<(Mphf, BuildStats), MphError>
+where
+    F: Fn(usize) -> &'k [u8] + Sync,
+{
+    assert!(n > 0, "empty key set is not supported");
+
+    cfg.validate()?;
+    let m = build_vertex_count(n, cfg)?;
+    scratch.graph.low_memory = cfg.low_memory_peel;
+
+    // Try different effective salts until the hypergraph peels fully.
+    let mut best_peeled = 0usize;
+    let mut degrees = DegreeStats::default();
+    let (first, last) = (
+        cfg.salt_round_start,
+        cfg.salt_round_start.saturating_add(cfg.rehash_limit),
+    );
+    for round in first..=last {
+        let salt = cfg.round_salt(round);
+        let attempt = if n < SMALL_TABLE_N {
+            try_build_small(n, &get, salt)
+        } else {
+            let bases = bases.filter(|_| round == first);
+            try_build_bdz(n, &get, bases, salt, m, cfg.require_minimal, scratch)
+        };
+        if round == first && n >= SMALL_TABLE_N {
+            degrees = scratch.graph.degrees;
+        }
+        match attempt {
+            Ok(mut mph) => {
+                mph.salt = salt;
+                if cfg.post_build_verify {
+                    verify_bijective(&mph, n, &get)?;
+                }
+                if cfg.fingerprint {
+                    mph.fingerprints = Some(fingerprint_table(&mph, n, &get));
+                }
+                // The scratch still holds this attempt's degrees.
+                let max_degree = if n < SMALL_TABLE_N {
+                    0
+                } else {
+                    scratch.graph.degrees.max
+                };
+                return Ok((
+                    mph,
+                    BuildStats {
+                        round,
+                        degrees,
+                        max_degree,
+                        padded: pads_gamma(n, cfg.gamma),
+                    },
+                ));
+            }
+            Err(MphError::Unresolvable { best_peeled: p, .. }) => {
+                best_peeled = best_peeled.max(p);
+                continue;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(MphError::Unresolvable {
+        rounds_tried: (last - first).saturating_add(1),
+        last_round: last,
+        best_peeled,
+        n,
+    })
+}
+
+/// Estimate the peak bytes of a BDZ build over `n` keys, for capacity planning.
+///
+/// Sums the per-attempt scratch (`rank` vertex arrays, CSR `deg`/`off`/`cur`/`edges`,
+/// peel queue, `peel_order`, `removed`), the output `g`, and the key list and dedup
+/// bookkeeping (24-byte `Vec` headers, 8-byte base hashes, an index table with its
+/// slack). The key bytes are not known here: add the total key length for the copy held.
+/// This crate's BDZ always uses `rank = 3`. Saturates at `usize::MAX` when `m` overflows
+/// or `n` exceeds [`MAX_KEYS`].
+/// Assumes the default CSR peel; `low_memory_peel` builds need less.
+pub fn estimate_build_memory(n: usize, gamma: f64, rank: u8) -> usize {
+    let Ok(m) = vertex_count(n, gamma) else {
+        return usize::MAX;
+    };
+    let m = m as usize;
+    let r = rank as usize;
+
+    let per_edge = 4 * r // vertex arrays
+        + 4 * r // CSR edges
+        + if cfg!(feature = "parallel") { 4 * r } else { 0 } // transient tuple buffer
+        + 8 // peel_order
+        + 1; // removed
+    let per_vertex: usize = 4 // deg
+        + 2 // q: ~m / 4 live entries, rounded up to a power of two
+        + 2 * 8 // off, cur
+        + 4; // g
+    // hashbrown keeps buckets at a power of two with load factor 7/8, 1 control byte each.
+    let set_buckets = (n.saturating_mul(8) / 7)
+        .max(4)
+        .checked_next_power_of_two()
+        .unwrap_or(usize::MAX);
+    let keys = n
+        .saturating_mul(24 + 8)
+        .saturating_add(set_buckets.saturating_mul(8 + 1));
+
+    per_edge
+        .saturating_mul(n)
+        .saturating_add(per_vertex.saturating_mul(m + 1))
+        .saturating_add(keys)
+}
+
+/// Collect keys and verify true uniqueness (no probabilistic deduplication).
+///
+/// The dedup table is keyed by `wyhash1(key, salt)`, which is exactly the base hash
+/// round 0 derives its vertices from (`salt` = the round-0 salt), so those hashes are
+/// kept and each key is hashed once for the common single-round build. Colliding
+/// hashes fall back to a byte comparison, so uniqueness stays exact.
+///
+/// With the "parallel" feature, inputs whose `size_hint` promises at least
+/// [`PAR_DEDUP_MIN`] keys go through [`collect_unique_par`] instead.
+fn collect_unique<K, I>(keys: I, salt: u64) -> Result<UniqueKeys, MphError>
+where
+    K: Borrow<[u8]>,
+    I: IntoIterator<Item = K>,
+{
+    let keys = keys.into_iter();
+    #[cfg(feature = "parallel")]
+    if keys.size_hint().0 >= PAR_DEDUP_MIN {
+        return collect_unique_par(keys.map(|k| k.borrow().to_vec()).collect(), salt);
+    }
+    let mut uniq = UniqueKeys {
+        keys: Vec::with_capacity(1024),
+        bases: Vec::with_capacity(1024),
+    };
+    let mut seen = hashbrown::HashTable::<usize>::new();
+    for k in keys {
+        let k = k.borrow();
+        let base = wyhash1(k, salt);
+        let UniqueKeys { keys, bases } = &uniq;
+        match seen.entry(base, |&i| keys[i] == k, |&i| bases[i]) {
+            hashbrown::hash_table::Entry::Occupied(_) => return Err(MphError::DuplicateKey),
+            hashbrown::hash_table::Entry::Vacant(slot) => {
+                slot.insert(keys.len());
+            }
+        }
+        uniq.keys.push(k.to_vec());
+        uniq.bases.push(base);
+    }
+    Ok(uniq)
 }
 
-impl Mphf {
-    #[inline]
-    pub fn index(&self, key: &[u8]) -> u64 {
-        let (a, b, c) = vertices(key, self.salt, self.m as u64);
-        // Safety: a,b,c < m; g.len() == m
-        let ga = unsafe { *self.g.get_unchecked(a as usize) };
-        let gb = unsafe { *self.g.get_unchecked(b as usize) };
-        let gc = unsafe { *self.g.get_unchecked(c as usize) };
-        ((ga + gb + gc) % (self.n as u32)) as u64
-    }
-    #[inline]
-    pub fn index_str(&self, s: &str) -> u64 {
-        self.index(s.as_bytes())
+/// Key count from which [`collect_unique`] dedups in parallel.
+#[cfg(feature = "parallel")]
+const PAR_DEDUP_MIN: usize = 1 << 17;
+
+/// log2 of the shard count of [`collect_unique_par`].
+#[cfg(feature = "parallel")]
+const DEDUP_SHARD_BITS: u32 = 8;
+
+/// [`collect_unique`] across cores, on keys already copied: hash them in parallel,
+/// split the indices into shards by the top [`DEDUP_SHARD_BITS`] of the hash, and
+/// check every shard for duplicates independently. Equal keys hash alike and so share
+/// a shard; within it a hash match is confirmed byte for byte, as in the serial path.
+#[cfg(feature = "parallel")]
+fn collect_unique_par(keys: Vec<Vec<u8>>, salt: u64) -> Result<UniqueKeys, MphError> {
+    use rayon::prelude::*;
+
+    // Shards index keys as u32; the build would reject this many keys anyway.
+    if keys.len() > MAX_KEYS {
+        return Err(MphError::TooManyVertices);
     }
+    let bases: Vec<u64> = keys.par_iter().map(|k| wyhash1(k, salt)).collect();
 
-    #[cfg(feature = "serde")]
-    pub fn to_bytes(&self) -> Result<Vec<u8>, MphError> {
-        Ok(bincode::serialize(self)?)
+    let mut shards = vec![Vec::<u32>::new(); 1 << DEDUP_SHARD_BITS];
+    for (i, &base) in bases.iter().enumerate() {
+        shards[(base >> (64 - DEDUP_SHARD_BITS)) as usize].push(i as u32);
     }
-    #[cfg(feature = "serde")]
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MphError> {
-        Ok(bincode::deserialize(bytes)?)
+    // Hashes in a shard share their top bits, which hashbrown uses as control tags;
+    // rotating them out keeps the tags informative.
+    let tag = |i: &u32| bases[*i as usize].rotate_left(DEDUP_SHARD_BITS);
+    let has_dup = shards.par_iter().any(|shard| {
+        let mut seen = hashbrown::HashTable::<u32>::with_capacity(shard.len());
+        shard.iter().any(|&i| {
+            let (h, k) = (tag(&i), &keys[i as usize]);
+            let eq = |j: &u32| tag(j) == h && keys[*j as usize] == *k;
+            match seen.entry(h, eq, tag) {
+                hashbrown::hash_table::Entry::Occupied(_) => true,
+                hashbrown::hash_table::Entry::Vacant(slot) => {
+                    slot.insert(i);
+                    false
+                }
+            }
+        })
+    });
+    if has_dup {
+        return Err(MphError::DuplicateKey);
     }
+    Ok(UniqueKeys { keys, bases })
 }
 
-/// Builder configuration.
-/// For huge datasets (e.g., 100M keys) set `gamma ≈ 1.27` to reduce rehash retries.
-#[derive(Debug, Clone)]
-pub struct BuildConfig {
-    /// Vertex ratio m/n; BDZ classic ~1.23. For 100M keys a good value is 1.27.
-    pub gamma: f64,
-    /// Maximum rehash attempts if the graph is not peelable.
-    pub rehash_limit: u32,
-    /// Base salt. Effective salts are derived deterministically.
-    pub salt: u64,
-}
-
-impl Default for BuildConfig {
-    fn default() -> Self {
-        Self {
-            gamma: 1.27,
-            rehash_limit: 16,
-            salt: 0xC0FF_EE00_D15E_A5E,
+/// [`collect_unique`] for owned keys, which are moved into the result instead of
+/// copied.
+fn unique_owned(keys: Vec<Vec<u8>>, salt: u64) -> Result<UniqueKeys, MphError> {
+    #[cfg(feature = "parallel")]
+    if keys.len() >= PAR_DEDUP_MIN {
+        return collect_unique_par(keys, salt);
+    }
+    let bases: Vec<u64> = keys.iter().map(|k| wyhash1(k, salt)).collect();
+    let mut seen = hashbrown::HashTable::<usize>::with_capacity(keys.len());
+    for (i, (k, &base)) in keys.iter().zip(&bases).enumerate() {
+        match seen.entry(base, |&j| keys[j] == *k, |&j| bases[j]) {
+            hashbrown::hash_table::Entry::Occupied(_) => return Err(MphError::DuplicateKey),
+            hashbrown::hash_table::Entry::Vacant(slot) => {
+                slot.insert(i);
+            }
         }
     }
+    Ok(UniqueKeys { keys, bases })
 }
 
-#[derive(Debug, Error)]
-pub enum MphError {
-    #[error("duplicate key detected during build")]
-    DuplicateKey,
-    #[error("graph was not peelable after rehash attempts")]
-    Unresolvable,
-    #[cfg(feature = "serde")]
-    #[error("serialization error: {0}")]
-    Serde(#[from] Box<bincode::ErrorKind>),
+/// Output of [`collect_unique`]: the keys plus their round-0 base hashes.
+struct UniqueKeys {
+    keys: Vec<Vec<u8>>,
+    bases: Vec<u64>,
 }
 
-pub struct Builder {
-    cfg: BuildConfig,
+/// Precompute BDZ vertices `(v0, v1, v2)` for every key under `salt` and `m`.
+///
+/// Each vertex is reduced `% m`, so the result is only reusable while `m` stays
+/// fixed: changing `gamma` (or the key count) changes `m` and invalidates it.
+/// Feed the output to [`Builder::build_from_vertices`] with the same `m` and `salt`.
+/// [`Mphf::reassign_g`] lists what else a fixed `salt` and `m` let a build reuse.
+pub fn prehash(keys: &[&[u8]], salt: u64, m: u64) -> (Vec<u32>, Vec<u32>, Vec<u32>) {
+    derive_vertices(keys, salt, m)
 }
 
-impl Builder {
-    pub fn new() -> Self {
-        Self {
-            cfg: BuildConfig::default(),
-        }
-    }
-    pub fn with_config(mut self, cfg: BuildConfig) -> Self {
-        self.cfg = cfg;
-        self
+/// Fingerprint of each of the `n` keys read through `get`, stored at its index.
+fn fingerprint_table<'k, F>(mph: &Mphf, n: usize, get: &F) -> Vec<u8>
+where
+    F: Fn(usize) -> &'k [u8],
+{
+    let mut fps = vec![0u8; mph.range() as usize];
+    for i in 0..n {
+        let k = get(i);
+        fps[mph.index(k) as usize] = fingerprint_byte(k, mph.salt);
     }
+    fps
+}
 
-    /// Build MPH from **unique** keys.
-    pub fn build<K, I>(self, keys: I) -> Result<Mphf, MphError>
-    where
-        K: Borrow<[u8]>,
-        I: IntoIterator<Item = K>,
-    {
-        // Collect and verify true uniqueness (no probabilistic deduplication).
-        let mut uniq = Vec::<Vec<u8>>::new();
-        uniq.reserve(1024);
-        let mut seen = HashSet::<Vec<u8>>::new();
-        for k in keys {
-            let v = k.borrow().to_vec();
-            if !seen.insert(v.clone()) {
-                return Err(MphError::DuplicateKey);
-            }
-            uniq.push(v);
-        }
-        let n = uniq.len();
-        assert!(n > 0, "empty key set is not supported");
-
-        // Try different effective salts until the hypergraph peels fully.
-        for round in 0..=self.cfg.rehash_limit {
-            let salt = mix_salt(self.cfg.salt, round);
-            match try_build_bdz(&uniq, n, salt, self.cfg.gamma) {
-                Ok(mut mph) => {
-                    mph.salt = salt;
-                    return Ok(mph);
-                }
-                Err(MphError::Unresolvable) => continue,
-                Err(e) => return Err(e),
-            }
+/// Check that the `n` keys read through `get` hit pairwise distinct indices.
+fn verify_bijective<'k, F>(mph: &Mphf, n: usize, get: &F) -> Result<(), MphError>
+where
+    F: Fn(usize) -> &'k [u8],
+{
+    let mut owner = vec![usize::MAX; mph.range() as usize];
+    for i in 0..n {
+        let index = mph.index(get(i));
+        let slot = &mut owner[index as usize];
+        if *slot != usize::MAX {
+            return Err(MphError::NotBijective {
+                index,
+                first: *slot,
+                second: i,
+            });
         }
-        Err(MphError::Unresolvable)
+        *slot = i;
     }
+    Ok(())
+}
+
+/// Version of the key hashing scheme: [`mph_hash`], [`vertex_triple`] and
+/// [`mph_fingerprint`]. Bumped whenever any of them changes output for the same
+/// input; an `Mphf` built under one version gives wrong indices under another.
+/// `to_bytes`, `to_binary` and `to_bytes_repr_c` stamp it into every blob, and their
+/// decoders refuse versions they have no lookup path for. `tests/golden_blob.rs` and
+/// `tests/golden_binary.rs` pin version 1.
+pub const HASH_VERSION: u32 = 1;
+
+/// The 64-bit key hash behind every lookup (wyhash v1 seeded with `salt`).
+///
+/// Stability-sensitive: pinned by [`HASH_VERSION`], not by semver.
+#[inline]
+pub fn mph_hash(key: &[u8], salt: u64) -> u64 {
+    wyhash1(key, salt)
+}
+
+/// Vertex triple `(a, b, c)`, each in `[0, m)`, that `Mphf::index` reads for `key`
+/// when called with `mph.salt()` and `mph.m`.
+///
+/// Stability-sensitive: pinned by [`HASH_VERSION`], not by semver.
+#[inline]
+pub fn vertex_triple(key: &[u8], salt: u64, m: u64) -> (u32, u32, u32) {
+    vertices(key, salt, m)
+}
+
+/// Fingerprint byte that `Mphf::index_if_member` compares against, for `mph.salt()`.
+///
+/// Stability-sensitive: pinned by [`HASH_VERSION`], not by semver.
+#[inline]
+pub fn mph_fingerprint(key: &[u8], salt: u64) -> u8 {
+    fingerprint_byte(key, salt)
 }
 
 /// One BDZ build attempt.
@@ -134,91 +1960,398 @@ impl Builder {
 /// 2) build CSR (deg/off/edges)
 /// 3) peel (queue vertices of degree 1)
 /// 4) assign g[] in reverse peel order
-fn try_build_bdz(keys: &[Vec<u8>], n: usize, salt: u64, gamma: f64) -> Result<Mphf, MphError> {
-    let n_u32 = n as u32;
-    let m = ((gamma * n as f64).ceil() as u32).max(1);
-
+///
+/// `bases`, if given, are the keys' `wyhash1` under `salt`, so step 1 skips hashing.
+fn try_build_bdz<'k, F>(
+    n: usize,
+    get: &F,
+    bases: Option<&[u64]>,
+    salt: u64,
+    m: u32,
+    minimal: bool,
+    scratch: &mut Scratch,
+) -> Result<Mphf, MphError>
+where
+    F: Fn(usize) -> &'k [u8] + Sync,
+{
     // 1) Derive vertices
-    let (v0, v1, v2) = derive_vertices(keys, salt, m as u64);
+    let Scratch { v0, v1, v2, graph } = scratch;
+    match bases {
+        Some(bases) => vertices_from_bases_into(bases, m as u64, [v0, v1, v2]),
+        None => derive_vertices_into(n, get, salt, m as u64, [v0, v1, v2]),
+    }
+
+    build_from_edges([v0, v1, v2], n, m, salt, minimal, graph)
+}
+
+/// Buffers of one build attempt. Every attempt clears and refills all of them, so
+/// nothing carries over between rounds, or between builds sharing a [`Builder`];
+/// only the capacity is reused.
+#[derive(Default)]
+struct Scratch {
+    v0: Vec<u32>,
+    v1: Vec<u32>,
+    v2: Vec<u32>,
+    graph: GraphScratch,
+}
+
+/// The part of [`Scratch`] used by [`build_from_edges`] (steps 2–4).
+#[derive(Default)]
+pub(crate) struct GraphScratch {
+    pub(crate) deg: Vec<u32>,
+    off: Vec<usize>,
+    cur: Vec<usize>,
+    edges: Vec<u32>,
+    q: VecDeque<u32>,
+    pub(crate) peel_order: Vec<Peel>,
+    pub(crate) removed: Vec<bool>,
+    inc_buf: Vec<u32>,
+    xor: Vec<u32>,        // XOR of incident edge ids per vertex, low-memory peel only
+    g: Vec<u32>,          // moved into the `Mphf` on success
+    degrees: DegreeStats, // of the last attempt, taken before peeling
+    /// Peel with [`peel_xor`] instead of building the CSR (`BuildConfig::low_memory_peel`).
+    low_memory: bool,
+}
+
+impl Scratch {
+    /// Drop the contents of every buffer, keeping the allocations.
+    fn clear(&mut self) {
+        let g = &mut self.graph;
+        for v in [
+            &mut self.v0,
+            &mut self.v1,
+            &mut self.v2,
+            &mut g.deg,
+            &mut g.edges,
+        ] {
+            v.clear();
+        }
+        g.q.clear();
+        for v in [&mut g.inc_buf, &mut g.xor, &mut g.g] {
+            v.clear();
+        }
+        g.off.clear();
+        g.cur.clear();
+        g.peel_order.clear();
+        g.removed.clear();
+    }
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct Peel {
+    pub(crate) edge: u32,
+    pub(crate) pivot: u8, // ∈ {0,1,2}
+}
+
+/// Below this many keys `gamma` is raised to at least [`SMALL_GAMMA`]. A single round
+/// at `gamma = 1.27` peels 30% of the time at 32 keys and 68% at 256, so under 256
+/// keys even 17 rounds fail too often (2e-7 at 200 keys, 2e-3 at 32); from 256 on
+/// they fail with probability below 1e-8.
+const SMALL_N: usize = 256;
+
+/// `gamma` floor for sets below [`SMALL_N`] keys: a single round peels 75% of the time
+/// at 32 keys, so 17 rounds all fail with probability about 5e-11.
+const SMALL_GAMMA: f64 = 1.5;
+
+/// Whether a build of `n` keys raises `gamma` to [`SMALL_GAMMA`] (sets below 32 keys
+/// store no graph and are never padded).
+pub(crate) fn pads_gamma(n: usize, gamma: f64) -> bool {
+    (SMALL_TABLE_N..SMALL_N).contains(&n) && gamma < SMALL_GAMMA
+}
+
+/// Largest key count a build accepts: lookups add three `g` entries, each below `n`,
+/// in `u32`.
+pub const MAX_KEYS: usize = (u32::MAX / 3) as usize;
+
+/// Vertex count `m` for `n` keys: `ceil(gamma * n)`, with `gamma` raised to
+/// [`SMALL_GAMMA`] for sets below [`SMALL_N`] keys.
+/// Fails instead of truncating when `m` does not fit in `u32`, or `n` exceeds [`MAX_KEYS`].
+pub(crate) fn vertex_count(n: usize, gamma: f64) -> Result<u32, MphError> {
+    if n > MAX_KEYS {
+        return Err(MphError::TooManyVertices);
+    }
+    let gamma = if pads_gamma(n, gamma) {
+        SMALL_GAMMA
+    } else {
+        gamma
+    };
+    let m = (gamma * n as f64).ceil();
+    if m > u32::MAX as f64 {
+        return Err(MphError::TooManyVertices);
+    }
+    let m = (m as u32).max(1);
+    check_address_space(n, m)?;
+    Ok(m)
+}
+
+/// [`vertex_count`] as a build under `cfg` uses it, i.e. with `prime_m` applied.
+pub(crate) fn build_vertex_count(n: usize, cfg: &BuildConfig) -> Result<u32, MphError> {
+    let m = vertex_count(n, cfg.gamma)?;
+    if !cfg.prime_m {
+        return Ok(m);
+    }
+    let m = (m as u64..=u32::MAX as u64)
+        .find(|&c| is_prime(c))
+        .ok_or(MphError::TooManyVertices)? as u32;
+    check_address_space(n, m)?;
+    Ok(m)
+}
+
+/// Trial division: at most 2^16 divisions for a `u32` candidate.
+fn is_prime(c: u64) -> bool {
+    c >= 2
+        && (2..)
+            .take_while(|d| d * d <= c)
+            .all(|d| !c.is_multiple_of(d))
+}
+
+/// Fail loudly when the largest build arrays (`3n` CSR edge slots, `m + 1` `usize`
+/// offsets) exceed the `isize::MAX` bytes a single allocation may span. Only 32-bit
+/// targets can get there; the arithmetic is done in `u64` on every target, so the
+/// check itself is compiled and type-checked on 64-bit hosts as well.
+fn check_address_space(n: usize, m: u32) -> Result<(), MphError> {
+    let edges = 3 * n as u64 * size_of::<u32>() as u64;
+    let offsets = (m as u64 + 1) * size_of::<usize>() as u64;
+    if edges.max(offsets) > isize::MAX as u64 {
+        return Err(MphError::AddressSpace { n, m });
+    }
+    Ok(())
+}
+
+/// `g` length (in entries, 1 MiB) from which [`Mphf::resolve_all`] prefetches; a
+/// smaller table stays cached and the prefetches only cost time.
+const PREFETCH_MIN_G: usize = 1 << 18;
+
+/// Key count below which builds store a sorted hash table instead of a BDZ `g`.
+/// At that size the graph is degenerate (peeling often needs several rounds) and its
+/// padded `g` (`m >= 1.5n`) outweighs one 32-bit hash per key.
+const SMALL_TABLE_N: usize = 32;
+
+/// Decoders' check of `n` against `m`: `n` in `1..=m`, and `m == n` only for a small
+/// set's hash table, which is how lookups tell the two representations apart.
+#[cfg(any(feature = "serde", feature = "binary"))]
+pub(crate) fn check_shape(n: u64, m: u32) -> Result<(), &'static str> {
+    if n == 0 || n > m as u64 {
+        return Err("n must be in 1..=m");
+    }
+    if n == m as u64 && n >= SMALL_TABLE_N as u64 {
+        return Err("m == n is reserved for sets below 32 keys");
+    }
+    Ok(())
+}
+
+/// The 32 bits of a key's base hash kept in a small set's table.
+#[inline]
+fn small_hash(base: u64) -> u32 {
+    (base >> 32) as u32
+}
+
+/// Rank of the key with base hash `base` in the sorted table `g`: the number of
+/// entries after the first that are `<=` its hash. Equals the key's position for
+/// members, and stays below `g.len()` (so in range) for any other key.
+#[inline]
+fn small_rank(g: &[u32], base: u64) -> u32 {
+    let h = small_hash(base);
+    let rest = g.get(1..).unwrap_or_default();
+    rest.iter().map(|&x| (x <= h) as u32).sum()
+}
+
+/// Small-set construction: sort the keys' 32-bit hashes. Fails (so the next round
+/// retries with another salt) only if two of them collide.
+fn try_build_small<'k, F>(n: usize, get: &F, salt: u64) -> Result<Mphf, MphError>
+where
+    F: Fn(usize) -> &'k [u8],
+{
+    let mut g: Vec<u32> = (0..n).map(|i| small_hash(wyhash1(get(i), salt))).collect();
+    g.sort_unstable();
+    if g.windows(2).any(|w| w[0] == w[1]) {
+        return Err(MphError::Unresolvable {
+            rounds_tried: 1,
+            last_round: 0,
+            best_peeled: 0,
+            n,
+        });
+    }
+    Ok(MphfParts {
+        n: n as u64,
+        m: n as u32,
+        salt,
+        g,
+        fingerprints: None,
+        remap: None,
+        minimal: true, // range n == m either way
+    }
+    .into())
+}
+
+/// Steps 2–4 of a BDZ build over already derived vertices (all `< m`).
+pub(crate) fn build_from_edges(
+    [v0, v1, v2]: [&[u32]; 3],
+    n: usize,
+    m: u32,
+    salt: u64,
+    minimal: bool,
+    scratch: &mut GraphScratch,
+) -> Result<Mphf, MphError> {
+    let n_u32 = n as u32;
 
-    // 2) Degrees and CSR
-    let mut deg = vec![0u32; m as usize];
+    // 2) Degrees
+    let deg = &mut scratch.deg;
+    deg.clear();
+    deg.resize(m as usize, 0);
     for i in 0..n {
         // SAFETY: vX[i] < m by construction
         unsafe {
-            *deg.get_unchecked_mut(v0[i] as usize) += 1;
-            *deg.get_unchecked_mut(v1[i] as usize) += 1;
-            *deg.get_unchecked_mut(v2[i] as usize) += 1;
+            *get_at_mut(deg, v0[i] as usize) += 1;
+            *get_at_mut(deg, v1[i] as usize) += 1;
+            *get_at_mut(deg, v2[i] as usize) += 1;
+        }
+    }
+
+    scratch.degrees = DegreeStats::from_degrees(deg);
+
+    // 3) Peeling
+    if scratch.low_memory {
+        peel_xor([v0, v1, v2], scratch);
+    } else {
+        peel_csr([v0, v1, v2], scratch);
+    }
+    let GraphScratch { peel_order, g, .. } = scratch;
+
+    if peel_order.len() != n {
+        return Err(MphError::Unresolvable {
+            rounds_tried: 1,
+            last_round: 0,
+            best_peeled: peel_order.len(),
+            n,
+        });
+    }
+
+    // 4) Assign g[] in reverse peel order
+    g.clear();
+    g.resize(m as usize, u32::MAX); // MAX => unassigned
+    for rec in peel_order.iter().rev() {
+        let e = rec.edge as usize;
+        let a = v0[e] as usize;
+        let b = v1[e] as usize;
+        let c = v2[e] as usize;
+
+        // Put unknown vertex first (pivot)
+        let (x, y, z) = match rec.pivot {
+            0 => (a, b, c),
+            1 => (b, a, c),
+            _ => (c, a, b),
+        };
+        let gy = if unsafe { *get_at(g, y) } == u32::MAX {
+            0
+        } else {
+            unsafe { *get_at(g, y) }
+        };
+        let gz = if unsafe { *get_at(g, z) } == u32::MAX {
+            0
+        } else {
+            unsafe { *get_at(g, z) }
+        };
+        let want = if minimal {
+            let sum = (gy + gz) % n_u32;
+            ((rec.edge % n_u32) + n_u32 - sum) % n_u32
+        } else {
+            // Lookup picks vertex #((g[a] + g[b] + g[c]) % 3) of (a, b, c): the pivot.
+            (rec.pivot as u32 + 3 - (gy + gz) % 3) % 3
+        };
+        unsafe {
+            *get_at_mut(g, x) = want;
+        }
+    }
+    for v in g.iter_mut() {
+        if *v == u32::MAX {
+            *v = 0;
         }
     }
 
+    Ok(MphfParts {
+        n: n as u64,
+        m,
+        salt,
+        g: std::mem::take(g),
+        fingerprints: None,
+        remap: None,
+        minimal,
+    }
+    .into())
+}
+
+/// Step 3 over a CSR adjacency (`off`/`edges`): a degree-1 vertex finds its live edge
+/// by scanning its incidence list and skipping removed edges.
+fn peel_csr([v0, v1, v2]: [&[u32]; 3], scratch: &mut GraphScratch) {
+    let GraphScratch {
+        deg,
+        off,
+        cur,
+        edges,
+        q,
+        peel_order,
+        removed,
+        inc_buf,
+        ..
+    } = scratch;
+    let n = v0.len();
+    let m = deg.len() as u32;
+
     // Prefix sums -> offsets
-    let mut off = vec![0usize; (m as usize) + 1];
+    off.clear();
+    off.resize(m as usize + 1, 0);
     for i in 0..m as usize {
         off[i + 1] = off[i] + deg[i] as usize;
     }
-    let mut cur = off.clone();
-    let mut edges = vec![0u32; off[m as usize]];
+    cur.clear();
+    cur.extend_from_slice(off);
+    edges.clear();
+    edges.resize(off[m as usize], 0);
 
     for eid in 0..n as u32 {
         let a = v0[eid as usize] as usize;
         let b = v1[eid as usize] as usize;
         let c = v2[eid as usize] as usize;
         unsafe {
-            let ia = *cur.get_unchecked(a);
+            let ia = *get_at(cur, a);
             edges[ia] = eid;
-            *cur.get_unchecked_mut(a) = ia + 1;
+            *get_at_mut(cur, a) = ia + 1;
 
-            let ib = *cur.get_unchecked(b);
+            let ib = *get_at(cur, b);
             edges[ib] = eid;
-            *cur.get_unchecked_mut(b) = ib + 1;
+            *get_at_mut(cur, b) = ib + 1;
 
-            let ic = *cur.get_unchecked(c);
+            let ic = *get_at(cur, c);
             edges[ic] = eid;
-            *cur.get_unchecked_mut(c) = ic + 1;
-        }
-    }
-
-    // 3) Peeling: queue of vertices with degree == 1
-    let mut q = Vec::with_capacity(m as usize);
-    for (vid, &d) in deg.iter().enumerate() {
-        if d == 1 {
-            q.push(vid as u32);
+            *get_at_mut(cur, c) = ic + 1;
         }
     }
-    let mut q_head = 0usize;
 
-    #[derive(Copy, Clone)]
-    struct Peel {
-        edge: u32,
-        pivot: u8,
-    } // pivot ∈ {0,1,2}
-    let mut peel_order = Vec::<Peel>::with_capacity(n);
-    let mut removed = vec![false; n]; // removed edges
+    // Peeling: queue of vertices with degree == 1
+    q.clear();
+    q.extend(degree_one(deg));
 
-    while q_head < q.len() {
-        let u = q[q_head];
-        q_head += 1;
+    peel_order.clear();
+    peel_order.reserve(n);
+    removed.clear();
+    removed.resize(n, false); // removed edges
 
+    while let Some(u) = q.pop_front() {
         // Iterate incident edges via CSR
-        let (start, end) = unsafe {
-            (
-                *off.get_unchecked(u as usize),
-                *off.get_unchecked(u as usize + 1),
-            )
-        };
+        let (start, end) = unsafe { (*get_at(off, u as usize), *get_at(off, u as usize + 1)) };
 
         // Collect live incident edges
-        let mut inc_buf: Vec<u32> = Vec::with_capacity(8);
+        inc_buf.clear();
         for i in start..end {
-            let e = unsafe { *edges.get_unchecked(i) };
-            if !unsafe { *removed.get_unchecked(e as usize) } {
+            let e = unsafe { *get_at(edges, i) };
+            if !unsafe { *get_at(removed, e as usize) } {
                 inc_buf.push(e);
             }
         }
 
-        for e in inc_buf {
-            if unsafe { *removed.get_unchecked(e as usize) } {
+        for &e in inc_buf.iter() {
+            if unsafe { *get_at(removed, e as usize) } {
                 continue;
             }
             let a = v0[e as usize];
@@ -226,11 +2359,11 @@ fn try_build_bdz(keys: &[Vec<u8>], n: usize, salt: u64, gamma: f64) -> Result<Mp
             let c = v2[e as usize];
 
             // Pivot is the current degree-1 endpoint of this edge
-            let pivot = if unsafe { *deg.get_unchecked(a as usize) } == 1 {
+            let pivot = if unsafe { *get_at(deg, a as usize) } == 1 {
                 0
-            } else if unsafe { *deg.get_unchecked(b as usize) } == 1 {
+            } else if unsafe { *get_at(deg, b as usize) } == 1 {
                 1
-            } else if unsafe { *deg.get_unchecked(c as usize) } == 1 {
+            } else if unsafe { *get_at(deg, c as usize) } == 1 {
                 2
             } else {
                 continue;
@@ -238,116 +2371,155 @@ fn try_build_bdz(keys: &[Vec<u8>], n: usize, salt: u64, gamma: f64) -> Result<Mp
 
             peel_order.push(Peel { edge: e, pivot });
             unsafe {
-                *removed.get_unchecked_mut(e as usize) = true;
+                *get_at_mut(removed, e as usize) = true;
             }
 
             match pivot {
                 0 => {
-                    dec_deg(&mut deg, b, &mut q);
-                    dec_deg(&mut deg, c, &mut q);
+                    dec_deg(deg, b, q);
+                    dec_deg(deg, c, q);
                 }
                 1 => {
-                    dec_deg(&mut deg, a, &mut q);
-                    dec_deg(&mut deg, c, &mut q);
+                    dec_deg(deg, a, q);
+                    dec_deg(deg, c, q);
                 }
                 _ => {
-                    dec_deg(&mut deg, a, &mut q);
-                    dec_deg(&mut deg, b, &mut q);
+                    dec_deg(deg, a, q);
+                    dec_deg(deg, b, q);
                 }
             }
         }
     }
+}
 
-    if peel_order.len() != n {
-        return Err(MphError::Unresolvable);
+/// Step 3 without the CSR: next to its degree, every vertex keeps the XOR of its
+/// incident edge ids, so at degree 1 that XOR *is* the remaining edge. An edge that
+/// repeats a vertex cancels out of that vertex's XOR while still counting twice in
+/// its degree, so the vertex cannot reach degree 1 through it and the XOR stays
+/// exact. Replaces `edges`, `off`, `cur`, `removed` and `inc_buf` (`13 * n + 16 * m`
+/// bytes) with the `4 * m` of `xor`.
+fn peel_xor([v0, v1, v2]: [&[u32]; 3], scratch: &mut GraphScratch) {
+    let GraphScratch {
+        deg,
+        xor,
+        q,
+        peel_order,
+        ..
+    } = scratch;
+    let n = v0.len();
+    xor.clear();
+    xor.resize(deg.len(), 0);
+    for e in 0..n {
+        // SAFETY: vX[e] < m by construction
+        unsafe {
+            *get_at_mut(xor, v0[e] as usize) ^= e as u32;
+            *get_at_mut(xor, v1[e] as usize) ^= e as u32;
+            *get_at_mut(xor, v2[e] as usize) ^= e as u32;
+        }
     }
 
-    // 4) Assign g[] in reverse peel order
-    let mut g = vec![u32::MAX; m as usize]; // MAX => unassigned
-    for rec in peel_order.iter().rev() {
-        let e = rec.edge as usize;
-        let a = v0[e] as usize;
-        let b = v1[e] as usize;
-        let c = v2[e] as usize;
+    q.clear();
+    q.extend(degree_one(deg));
+    peel_order.clear();
+    peel_order.reserve(n);
 
-        // Put unknown vertex first (pivot)
-        let (x, y, z) = match rec.pivot {
-            0 => (a, b, c),
-            1 => (b, a, c),
-            _ => (c, a, b),
-        };
-        let gy = if unsafe { *g.get_unchecked(y) } == u32::MAX {
-            0
-        } else {
-            unsafe { *g.get_unchecked(y) }
-        };
-        let gz = if unsafe { *g.get_unchecked(z) } == u32::MAX {
-            0
-        } else {
-            unsafe { *g.get_unchecked(z) }
-        };
-        let sum = (gy + gz) % n_u32;
-        let want = ((rec.edge % n_u32) + n_u32 - sum) % n_u32;
-        unsafe {
-            *g.get_unchecked_mut(x) = want;
+    // A vertex is queued once, when its degree first drops to 1; by the time it is
+    // popped its edge may have been peeled through another vertex.
+    while let Some(u) = q.pop_front() {
+        if unsafe { *get_at(deg, u as usize) } != 1 {
+            continue;
         }
-    }
-    for v in &mut g {
-        if *v == u32::MAX {
-            *v = 0;
+        let e = unsafe { *get_at(xor, u as usize) };
+        let tri = [v0[e as usize], v1[e as usize], v2[e as usize]];
+        let pivot = tri.iter().position(|&v| v == u).unwrap() as u8;
+        peel_order.push(Peel { edge: e, pivot });
+        for v in tri {
+            unsafe {
+                *get_at_mut(xor, v as usize) ^= e;
+            }
+            dec_deg(deg, v, q);
         }
     }
+}
 
-    Ok(Mphf {
-        n: n as u64,
-        m,
-        salt,
-        g,
-    })
+/// Vertices of degree 1, the initial peel queue.
+fn degree_one(deg: &[u32]) -> impl Iterator<Item = u32> + '_ {
+    deg.iter()
+        .enumerate()
+        .filter(|&(_, &d)| d == 1)
+        .map(|(v, _)| v as u32)
 }
 
 #[inline]
-fn dec_deg(deg: &mut [u32], v: u32, q: &mut Vec<u32>) {
+fn dec_deg(deg: &mut [u32], v: u32, q: &mut VecDeque<u32>) {
     // SAFETY: v < deg.len()
-    let d = unsafe { deg.get_unchecked_mut(v as usize) };
+    let d = unsafe { get_at_mut(deg, v as usize) };
     if *d > 0 {
         *d -= 1;
         if *d == 1 {
-            q.push(v);
+            q.push_back(v);
         }
     }
 }
 
-/// Derive 3 vertices for each key (possibly in parallel if the "rayon" feature is enabled).
-fn derive_vertices(keys: &[Vec<u8>], salt: u64, m: u64) -> (Vec<u32>, Vec<u32>, Vec<u32>) {
-    #[cfg(feature = "rayon")]
+/// Derive 3 vertices for each key (possibly in parallel if the "parallel" feature is enabled).
+fn derive_vertices<K: AsRef<[u8]> + Sync>(
+    keys: &[K],
+    salt: u64,
+    m: u64,
+) -> (Vec<u32>, Vec<u32>, Vec<u32>) {
+    derive_vertices_with(keys.len(), &|i| keys[i].as_ref(), salt, m)
+}
+
+/// [`derive_vertices`] over keys read through `get(i)` for `i in 0..n`.
+pub(crate) fn derive_vertices_with<'k, F>(
+    n: usize,
+    get: &F,
+    salt: u64,
+    m: u64,
+) -> (Vec<u32>, Vec<u32>, Vec<u32>)
+where
+    F: Fn(usize) -> &'k [u8] + Sync,
+{
+    let (mut v0, mut v1, mut v2) = (Vec::new(), Vec::new(), Vec::new());
+    derive_vertices_into(n, get, salt, m, [&mut v0, &mut v1, &mut v2]);
+    (v0, v1, v2)
+}
+
+/// [`derive_vertices_with`] into existing buffers, overwriting their contents.
+fn derive_vertices_into<'k, F>(n: usize, get: &F, salt: u64, m: u64, out: [&mut Vec<u32>; 3])
+where
+    F: Fn(usize) -> &'k [u8] + Sync,
+{
+    let [v0, v1, v2] = out;
+    for v in [&mut *v0, &mut *v1, &mut *v2] {
+        v.clear();
+        v.resize(n, 0);
+    }
+    #[cfg(feature = "parallel")]
     {
         use rayon::prelude::*;
-        let verts: Vec<(u32, u32, u32)> = keys.par_iter().map(|k| vertices(k, salt, m)).collect();
-        let n = verts.len();
-        let mut v0 = Vec::with_capacity(n);
-        let mut v1 = Vec::with_capacity(n);
-        let mut v2 = Vec::with_capacity(n);
-        for (a, b, c) in verts {
-            v0.push(a);
-            v1.push(b);
-            v2.push(c);
-        }
-        (v0, v1, v2)
-    }
-    #[cfg(not(feature = "rayon"))]
-    {
-        let n = keys.len();
-        let mut v0 = Vec::with_capacity(n);
-        let mut v1 = Vec::with_capacity(n);
-        let mut v2 = Vec::with_capacity(n);
-        for k in keys {
-            let (a, b, c) = vertices(k, salt, m);
-            v0.push(a);
-            v1.push(b);
-            v2.push(c);
-        }
-        (v0, v1, v2)
+        v0.par_iter_mut()
+            .zip(v1.par_iter_mut())
+            .zip(v2.par_iter_mut())
+            .enumerate()
+            .for_each(|(i, ((a, b), c))| (*a, *b, *c) = vertices(get(i), salt, m));
+    }
+    #[cfg(not(feature = "parallel"))]
+    for i in 0..n {
+        (v0[i], v1[i], v2[i]) = vertices(get(i), salt, m);
+    }
+}
+
+/// [`derive_vertices_into`] from precomputed base hashes (`wyhash1(key, salt)`).
+fn vertices_from_bases_into(bases: &[u64], m: u64, out: [&mut Vec<u32>; 3]) {
+    let [v0, v1, v2] = out;
+    for v in [&mut *v0, &mut *v1, &mut *v2] {
+        v.clear();
+        v.resize(bases.len(), 0);
+    }
+    for (i, &base) in bases.iter().enumerate() {
+        (v0[i], v1[i], v2[i]) = vertices_from_base(base, m);
     }
 }
 
@@ -355,21 +2527,53 @@ fn derive_vertices(keys: &[Vec<u8>], salt: u64, m: u64) -> (Vec<u32>, Vec<u32>,
 /// This is faster than running 3× hash per key and sufficient for BDZ.
 #[inline]
 fn vertices(key: &[u8], salt: u64, m: u64) -> (u32, u32, u32) {
-    let base = wyhash1(key, salt);
+    vertices_from_base(wyhash1(key, salt), m)
+}
+
+#[inline]
+fn vertices_from_base(base: u64, m: u64) -> (u32, u32, u32) {
     let a = splitmix64(base ^ 0x9E37_79B9_7F4A_7C15) % m;
     let b = splitmix64(base.wrapping_add(0xA24B_1F6F)) % m;
     let c = splitmix64(base ^ 0x853C_49E6_0A6C_9D39) % m;
     (a as u32, b as u32, c as u32)
 }
 
+/// `M = ceil(2^64 / d)`; zero for `d <= 1`, where `fastmod` then yields 0 as required.
+#[inline]
+fn fastmod_constant(d: u32) -> u64 {
+    if d <= 1 {
+        0
+    } else {
+        (u64::MAX / d as u64).wrapping_add(1)
+    }
+}
+
+/// `a % d` without a division (Lemire, Kaser & Kurz), exact for all 32-bit `a` and `d`.
+#[inline]
+fn fastmod(a: u32, m: u64, d: u32) -> u32 {
+    let low = m.wrapping_mul(a as u64);
+    ((low as u128 * d as u128) >> 64) as u32
+}
+
+/// Seed tweak separating fingerprints from the vertex hash of the same key.
+pub(crate) const FINGERPRINT_SEED: u64 = 0x6A09_E667_F3BC_C908;
+
+#[inline]
+fn fingerprint_byte(key: &[u8], salt: u64) -> u8 {
+    (wyhash1(key, salt ^ FINGERPRINT_SEED) & 0xFF) as u8
+}
+
 #[inline]
-fn wyhash1(data: &[u8], seed: u64) -> u64 {
+pub(crate) fn wyhash1(data: &[u8], seed: u64) -> u64 {
     wyhash::wyhash(data, seed)
 }
 
+/// 2^64 / φ, the splitmix64 stream increment.
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
 #[inline]
 fn splitmix64(mut x: u64) -> u64 {
-    x = x.wrapping_add(0x9E3779B97F4A7C15);
+    x = x.wrapping_add(GOLDEN_GAMMA);
     let mut z = x;
     z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
     z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
@@ -377,12 +2581,56 @@ fn splitmix64(mut x: u64) -> u64 {
 }
 
 /// Deterministically tweak base salt by round (FNV-like).
+///
+/// The round is spread over all 64 bits before it meets the base: XORing it in raw
+/// made bases that differ only in their low bits (0 and 1, say) retry each other's
+/// salts. Round 0 is unaffected.
 #[inline]
 fn mix_salt(base: u64, round: u32) -> u64 {
     const FNV_OFFSET: u64 = 0xcbf29ce484222325;
     const FNV_PRIME: u64 = 0x100000001b3;
     let mut h = FNV_OFFSET ^ base;
-    h ^= round as u64;
+    h ^= (round as u64).wrapping_mul(GOLDEN_GAMMA);
     h = h.wrapping_mul(FNV_PRIME);
     h ^ (h >> 33)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn reduce(a: u32, d: u32) -> u32 {
+        fastmod(a, fastmod_constant(d), d)
+    }
+
+    #[test]
+    fn fastmod_matches_remainder_at_the_edges() {
+        let mut divisors = vec![1, 2, 3, 7, u32::MAX / 3, u32::MAX - 1, u32::MAX];
+        divisors.extend((1..32).map(|k| 1u32 << k));
+        divisors.extend((2..32).map(|k| (1u32 << k) - 1));
+        divisors.extend((1..32).map(|k| (1u32 << k) + 1));
+        let mut rng = StdRng::seed_from_u64(356);
+        for &d in &divisors {
+            let mut dividends = vec![0, 1, d - 1, d, u32::MAX - 1, u32::MAX];
+            dividends.extend(d.checked_add(1));
+            dividends.extend(d.checked_mul(2));
+            dividends.extend((0..200).map(|_| rng.r#gen::<u32>()));
+            for a in dividends {
+                assert_eq!(reduce(a, d), a % d, "{a} % {d}");
+            }
+        }
+    }
+
+    #[test]
+    fn fastmod_matches_remainder_for_random_divisors() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..100_000 {
+            let d = rng.gen_range(1..=u32::MAX);
+            let a = rng.r#gen::<u32>();
+            assert_eq!(reduce(a, d), a % d, "{a} % {d}");
+            assert_eq!(reduce(u32::MAX, d), u32::MAX % d, "u32::MAX % {d}");
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Hand-written binary codec for [`Mphf`], independent of serde/bincode.
+//!
+//! Layout (all integers little-endian):
+//!
+//! | field          | size           |
+//! |----------------|----------------|
+//! | magic `MPHB`   | 4              |
+//! | hash version   | 1              |
+//! | flags          | 1              |
+//! | `n`            | 8              |
+//! | `m`            | 4              |
+//! | `salt`         | 8              |
+//! | `g`            | 4 × `m`        |
+//! | fingerprints   | 1 × `range`, if flag bit 1 |
+//! | remap          | 4 × `range`, if flag bit 2 |
+//!
+//! The version byte is [`HASH_VERSION`], as in the bincode blobs of `to_bytes`. Flag
+//! bit 0 is set for minimal builds. `range` is `n` when minimal, `m` otherwise.
+
+use crate::bdz::{HASH_VERSION, MphError, Mphf, MphfParts, check_shape};
+
+const MAGIC: [u8; 4] = *b"MPHB";
+
+const FLAG_MINIMAL: u8 = 1;
+const FLAG_FINGERPRINTS: u8 = 1 << 1;
+const FLAG_REMAP: u8 = 1 << 2;
+
+impl Mphf {
+    /// Encode in the fixed layout of the `binary` feature (no serde involved). Not
+    /// interchangeable with the bincode bytes of `to_bytes`.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let range = self.range() as usize;
+        let mut flags = 0;
+        if self.minimal {
+            flags |= FLAG_MINIMAL;
+        }
+        if self.fingerprints.is_some() {
+            flags |= FLAG_FINGERPRINTS;
+        }
+        if self.remap.is_some() {
+            flags |= FLAG_REMAP;
+        }
+
+        let mut out = Vec::with_capacity(26 + 4 * self.g.len() + 5 * range);
+        out.extend_from_slice(&MAGIC);
+        out.push(HASH_VERSION as u8);
+        out.push(flags);
+        out.extend_from_slice(&self.n.to_le_bytes());
+        out.extend_from_slice(&self.m.to_le_bytes());
+        out.extend_from_slice(&self.salt.to_le_bytes());
+        for &v in &self.g {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        if let Some(fps) = &self.fingerprints {
+            out.extend_from_slice(fps);
+        }
+        if let Some(remap) = &self.remap {
+            for &v in remap {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        out
+    }
+
+    /// Decode bytes written by [`to_binary`](Self::to_binary).
+    ///
+    /// Every structural invariant `index` relies on is checked (table lengths,
+    /// `n <= m` with `m == n` only below 32 keys, remap entries in range), so the
+    /// result is safe to query even when the bytes come from an untrusted source. A
+    /// blob stamped with another [`HASH_VERSION`] fails with
+    /// [`MphError::UnsupportedHashVersion`].
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, MphError> {
+        let mut r = Reader(bytes);
+        if r.take(4)? != MAGIC {
+            return Err(MphError::Binary("bad magic"));
+        }
+        let version = r.u8()?;
+        if version as u32 != HASH_VERSION {
+            return Err(MphError::UnsupportedHashVersion(version));
+        }
+        let flags = r.u8()?;
+        if flags & !(FLAG_MINIMAL | FLAG_FINGERPRINTS | FLAG_REMAP) != 0 {
+            return Err(MphError::Binary("unknown flags"));
+        }
+        let n = r.u64()?;
+        let m = r.u32()?;
+        let salt = r.u64()?;
+        check_shape(n, m).map_err(MphError::Binary)?;
+        let minimal = flags & FLAG_MINIMAL != 0;
+        let range = if minimal { n } else { m as u64 } as usize;
+
+        let g = r.u32s(m as usize)?;
+        let fingerprints = if flags & FLAG_FINGERPRINTS != 0 {
+            Some(r.take(range)?.to_vec())
+        } else {
+            None
+        };
+        let remap = if flags & FLAG_REMAP != 0 {
+            let remap = r.u32s(range)?;
+            if remap.iter().any(|&i| i as usize >= range) {
+                return Err(MphError::Binary("remap entry out of range"));
+            }
+            Some(remap)
+        } else {
+            None
+        };
+        if !r.0.is_empty() {
+            return Err(MphError::Binary("trailing bytes"));
+        }
+
+        Ok(MphfParts {
+            n,
+            m,
+            salt,
+            g,
+            fingerprints,
+            minimal,
+            remap,
+        }
+        .into())
+    }
+}
+
+/// Cursor over the input; every read fails with `Binary("truncated")` past the end.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MphError> {
+        if self.0.len() < len {
+            return Err(MphError::Binary("truncated"));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], MphError> {
+        Ok(self.take(N)?.try_into().expect("take returned N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, MphError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, MphError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, MphError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn u32s(&mut self, count: usize) -> Result<Vec<u32>, MphError> {
+        let len = count
+            .checked_mul(4)
+            .ok_or(MphError::Binary("length overflow"))?;
+        let raw = self.take(len)?;
+        Ok(raw
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().expect("4-byte chunk")))
+            .collect())
+    }
+}
@@ -1,28 +1,99 @@
-use crate::hash::KeyHash;
-use crate::util::BitSet;
+use crate::hash::{KeyHash, Placement};
+use crate::util::{Avx2, BitSet, PackedArray, bit_width};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
+use std::cmp::Reverse;
 use std::collections::HashSet;
 use thiserror::Error;
 
 /// Final MPH structure: stores the set size, number of buckets, salt, and per-bucket displacements.
+///
+/// `Mphf` is `Send + Sync`; lookups are read-only and safe to run concurrently.
+///
+/// Deserialization checks that `n` and `buckets` are non-zero and that there is one
+/// displacement per bucket.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawMphf"))]
 #[derive(Debug, Clone)]
 pub struct Mphf {
     pub n: u64,
     pub buckets: u64,
     pub salt: u64,
-    pub disps: Vec<u64>, // len == buckets
+    pub disps: PackedArray, // len == buckets, packed to the widest displacement
+    pub placement: Placement, // strategy the displacements were searched with
+    /// Effective salt of the bucket selector, when it differs from `salt`
+    /// (see `BuildConfig::bucket_salt`).
+    pub bucket_salt: Option<u64>,
 }
 
+/// Unvalidated serialized form of [`Mphf`].
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawMphf {
+    n: u64,
+    buckets: u64,
+    salt: u64,
+    disps: PackedArray,
+    placement: Placement,
+    bucket_salt: Option<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawMphf> for Mphf {
+    type Error = String;
+
+    fn try_from(raw: RawMphf) -> Result<Self, Self::Error> {
+        if raw.n == 0 || raw.buckets == 0 {
+            return Err("n and buckets must be non-zero".into());
+        }
+        if raw.disps.len() as u64 != raw.buckets {
+            return Err("displacement count does not match buckets".into());
+        }
+        Ok(Self {
+            n: raw.n,
+            buckets: raw.buckets,
+            salt: raw.salt,
+            disps: raw.disps,
+            placement: raw.placement,
+            bucket_salt: raw.bucket_salt,
+        })
+    }
+}
+
+// Compile-time guard for the `Send + Sync` guarantee above.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Mphf>();
+};
+
 impl Mphf {
-    /// O(1) lookup. Uses the same formula as the builder.
+    /// Effective salt the key hashes were derived with (base salt mixed with the round).
+    #[inline]
+    pub fn salt(&self) -> u64 {
+        self.salt
+    }
+
+    /// O(1) lookup. Uses the same formula as the builder. Panics (never reads out of
+    /// bounds) if the public fields were edited so that `buckets != disps.len()`.
     #[inline]
     pub fn index(&self, key: &[u8]) -> u64 {
-        let kh = KeyHash::from_key(key, self.salt);
-        let b = kh.bucket(self.buckets);
-        let d = unsafe { *self.disps.get_unchecked(b) };
-        kh.place(self.n, d) as u64
+        self.index_prehashed(&self.key_hash(key))
+    }
+
+    /// The hashes `index` derives from `key`: `KeyHash::from_key(key, mph.salt())`, with
+    /// `h1` from `bucket_salt` instead when one is set.
+    #[inline]
+    pub fn key_hash(&self, key: &[u8]) -> KeyHash {
+        KeyHash::from_key_split(key, self.bucket_salt.unwrap_or(self.salt), self.salt)
+    }
+
+    /// Lookup from a hash computed once with [`key_hash`](Self::key_hash), e.g. to
+    /// share it with other structures keyed on the same salt.
+    #[inline]
+    pub fn index_prehashed(&self, kh: &KeyHash) -> u64 {
+        let d = self.disps.get(kh.bucket(self.buckets));
+        kh.place_with(self.placement, self.n, d) as u64
     }
 
     #[inline]
@@ -30,28 +101,90 @@ impl Mphf {
         self.index(s.as_bytes())
     }
 
+    /// Space taken by the displacements, e.g. to weigh CHD against BDZ on the same
+    /// keys (a BDZ `Mphf` stores `m` 32-bit entries: `32 * m / n` bits per key).
+    /// Walks every bucket once.
+    pub fn space_stats(&self) -> SpaceStats {
+        let n = self.n.max(1) as f64;
+        let (mut sum, mut max) = (0u64, 0u64);
+        for b in 0..self.disps.len() {
+            let d = self.disps.get(b);
+            sum += d;
+            max = max.max(d);
+        }
+        SpaceStats {
+            buckets: self.buckets,
+            avg_disp: sum as f64 / self.disps.len().max(1) as f64,
+            max_disp: max,
+            disp_bits: self.disps.width(),
+            packed_bits_per_key: self.disps.width() as f64 * self.disps.len() as f64 / n,
+            bits_per_key: (self.disps.size_bytes() * 8) as f64 / n,
+        }
+    }
+
+    /// Release excess capacity of the packed displacements.
+    pub fn shrink_to_fit(&mut self) {
+        self.disps.shrink_to_fit();
+    }
+
     #[cfg(feature = "serde")]
     pub fn to_bytes(&self) -> Result<Vec<u8>, MphError> {
         Ok(bincode::serialize(self)?)
     }
 
+    /// Decode bytes written by [`to_bytes`](Self::to_bytes). Inconsistent fields (see
+    /// the type docs) fail with `Serde`, so the result is safe to query.
     #[cfg(feature = "serde")]
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, MphError> {
         Ok(bincode::deserialize(bytes)?)
     }
 }
 
-/// Build parameters.
+/// Build parameters. With the `serde` feature, fields missing from a serialized
+/// config take their [`Default`] values.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Debug, Clone)]
 pub struct BuildConfig {
     /// Target average bucket size. Smaller → easier placement, but more buckets (overhead).
     pub target_bucket_size: f64,
     /// How many seeds/displacements to try for a bucket before declaring failure and rehashing.
     pub max_seed_attempts: u32,
-    /// Base salt (re-hash deterministically mixes in the round).
+    /// Base salt (re-hash deterministically mixes in the round). `0` is an ordinary
+    /// seed: the round mix turns it into a nonzero salt like any other value.
     pub salt: u64,
     /// How many different salts (rounds) to try before giving up.
     pub rehash_limit: u32,
+    /// Give up on a bucket (and rehash) once this many pseudo-random displacements
+    /// collided after the linear scan, instead of grinding to `max_seed_attempts`.
+    /// `None` keeps `max_seed_attempts` as the only bound.
+    pub max_random_attempts: Option<u32>,
+    /// Seed for the displacement-search PRNG, independent of `salt`. The same seed is
+    /// used in every rehash round. `None` derives it from the round's salt.
+    pub prng_seed: Option<u64>,
+    /// How a displacement maps a key to its slot; recorded in the built `Mphf`.
+    pub placement: Placement,
+    /// Search displacements for batches of buckets in parallel (requires the "parallel"
+    /// feature; `InvalidConfig` without it). Deterministic for a given salt whatever the
+    /// thread count, but yields different displacements than the sequential path.
+    pub parallel_placement: bool,
+    /// Exact bucket count, overriding the one derived from `target_bucket_size`.
+    /// A power of two lets lookups pick the bucket with a mask instead of `%`.
+    pub buckets: Option<u64>,
+    /// Separate base salt for the bucket selector `h1`, mixed with the round like
+    /// `salt`, so bucketing and placement can be re-seeded independently; recorded in
+    /// the built `Mphf`. `None` derives all three hashes from `salt`.
+    pub bucket_salt: Option<u64>,
+    /// Replace the fixed `max_seed_attempts` by a per-bucket cap of this many times
+    /// the bucket's expected attempt count `(n / free)^size`, where `free` is the
+    /// number of slots still open when it is placed (at most 2^24, the number of
+    /// distinct random displacements; `max_random_attempts` still applies). Large
+    /// early buckets then give up on a bad salt after a handful of tries, while the
+    /// singletons placed into a nearly full table get the ~`n` tries they need, which
+    /// a fixed 50 000 denies them once `n` is larger. A bucket that needs more than
+    /// `c` times its expectation is rare (probability about `e^-c`), so keep `c` near
+    /// `ln(buckets) + 8`, e.g. 16–24. `None` uses `max_seed_attempts` for every bucket.
+    pub attempt_scale: Option<f64>,
 }
 
 impl Default for BuildConfig {
@@ -59,27 +192,124 @@ impl Default for BuildConfig {
         Self {
             target_bucket_size: 4.0,
             max_seed_attempts: 50_000,
-            salt: 0xC0FF_EE00_D15E_A5E,
+            salt: 0x0C0F_FEE0_0D15_EA5E,
             rehash_limit: 6,
+            max_random_attempts: None,
+            prng_seed: None,
+            placement: Placement::Linear,
+            parallel_placement: false,
+            buckets: None,
+            bucket_salt: None,
+            attempt_scale: None,
         }
     }
 }
 
+impl BuildConfig {
+    /// Reject configurations that cannot build, e.g. a non-finite or non-positive
+    /// `target_bucket_size` (NaN would otherwise silently yield a single bucket), a
+    /// zero `buckets`, or `parallel_placement` without the "parallel" feature.
+    pub fn validate(&self) -> Result<(), MphError> {
+        if !(self.target_bucket_size.is_finite() && self.target_bucket_size > 0.0) {
+            return Err(MphError::InvalidConfig(format!(
+                "target_bucket_size must be finite and > 0, got {}",
+                self.target_bucket_size
+            )));
+        }
+        if let Some(c) = self.attempt_scale.filter(|c| !(c.is_finite() && *c > 0.0)) {
+            return Err(MphError::InvalidConfig(format!(
+                "attempt_scale must be finite and > 0, got {c}"
+            )));
+        }
+        if let Some(b) = self.buckets.filter(|&b| b == 0 || usize::try_from(b).is_err()) {
+            return Err(MphError::InvalidConfig(format!(
+                "buckets must be in 1..=usize::MAX, got {b}"
+            )));
+        }
+        if self.parallel_placement && !cfg!(feature = "parallel") {
+            return Err(MphError::InvalidConfig(
+                "parallel_placement requires the \"parallel\" feature".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Total displacement attempts allowed for a bucket of `size` keys placed while
+    /// `free` of the `n` slots are open.
+    fn attempt_limit(&self, size: usize, free: u64, n: u64) -> u32 {
+        let cap = match self.attempt_scale {
+            Some(c) => {
+                let expected = (n as f64 / free.max(1) as f64).powi(size as i32);
+                (c * expected).clamp(1.0, MAX_SCALED_ATTEMPTS as f64).ceil() as u32
+            }
+            None => self.max_seed_attempts,
+        };
+        match self.max_random_attempts {
+            Some(k) => cap.min(LINEAR_DISPS.saturating_add(k)),
+            None => cap,
+        }
+    }
+}
+
+/// Statistics collected by [`Builder::build_with_stats`].
+#[derive(Debug, Clone, Default)]
+pub struct BuildStats {
+    /// Rehash round (in `0..=rehash_limit`) whose salt placed every bucket.
+    pub round: u32,
+    /// Bits per stored displacement (width of the widest one).
+    pub disp_bits: u8,
+    /// Average minimal width of the displacements, in bits.
+    pub avg_disp_bits: f64,
+}
+
+/// Result of [`Mphf::space_stats`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpaceStats {
+    /// Bucket count, about `n / target_bucket_size`.
+    pub buckets: u64,
+    /// Mean and largest displacement over all buckets (empty ones count as 0).
+    pub avg_disp: f64,
+    pub max_disp: u64,
+    /// Bits per stored displacement (width of the widest one).
+    pub disp_bits: u8,
+    /// `disp_bits * buckets / n`: what the packing itself costs per key.
+    pub packed_bits_per_key: f64,
+    /// Heap bits of the displacement words per key, i.e. `packed_bits_per_key` plus
+    /// the rounding to whole 64-bit words.
+    pub bits_per_key: f64,
+}
+
 #[derive(Debug, Error)]
 pub enum MphError {
     #[error("duplicate key detected during build")]
     DuplicateKey,
     #[error("could not place all buckets after rehash attempts")]
     Unresolvable,
+    #[error("invalid build configuration: {0}")]
+    InvalidConfig(String),
+    #[error("empty key set is not supported")]
+    EmptyKeySet,
     #[cfg(feature = "serde")]
     #[error("serialization error: {0}")]
     Serde(#[from] Box<bincode::ErrorKind>),
 }
 
+// Same guarantee as the BDZ `MphError`: usable as `anyhow::Error` and across `.await`.
+const _: () = {
+    const fn assert_error<T: std::error::Error + Send + Sync + 'static>() {}
+    assert_error::<MphError>();
+};
+
 pub struct Builder {
     cfg: BuildConfig,
 }
 
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Builder {
     pub fn new() -> Self {
         Self { cfg: BuildConfig::default() }
@@ -96,6 +326,17 @@ impl Builder {
         K: Borrow<[u8]>,
         I: IntoIterator<Item = K>,
     {
+        self.build_with_stats(keys).map(|(mph, _)| mph)
+    }
+
+    /// Same as [`build`](Self::build), also returning [`BuildStats`].
+    pub fn build_with_stats<K, I>(self, keys: I) -> Result<(Mphf, BuildStats), MphError>
+    where
+        K: Borrow<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        self.cfg.validate()?;
+
         // 0) Collect and validate uniqueness using the exact bytes (no probabilistic hashes).
         let mut uniq = Vec::<Vec<u8>>::new();
         let mut seen = HashSet::<Vec<u8>>::new();
@@ -107,15 +348,35 @@ impl Builder {
             uniq.push(v);
         }
         let n = uniq.len();
-        assert!(n > 0, "empty key set is not supported");
+        if n == 0 {
+            return Err(MphError::EmptyKeySet);
+        }
 
-        // 1) Several attempts with different salts.
+        // 1) Several attempts with different salts, sharing the scratch buffers.
+        let buckets_cnt = bucket_count(n, &self.cfg);
+        let mut scratch = Scratch::new(n, buckets_cnt);
         for round in 0..=self.cfg.rehash_limit {
             let salt = mix_salt(self.cfg.salt, round);
-            match try_build_once(&uniq, n, salt, &self.cfg) {
-                Ok(mut mph) => {
-                    mph.salt = salt;
-                    return Ok(mph);
+            let bucket_salt = self.cfg.bucket_salt.map(|b| mix_salt(b, round));
+            let salts = (bucket_salt.unwrap_or(salt), salt);
+            match try_build_once(&uniq, n, salts, &self.cfg, &mut scratch) {
+                Ok(()) => {
+                    let raw = &scratch.disps;
+                    let disps = PackedArray::from_slice(raw);
+                    let stats = BuildStats {
+                        round,
+                        disp_bits: disps.width(),
+                        avg_disp_bits: avg_disp_bits(raw),
+                    };
+                    let mph = Mphf {
+                        n: n as u64,
+                        buckets: disps.len() as u64,
+                        salt,
+                        disps,
+                        placement: self.cfg.placement,
+                        bucket_salt,
+                    };
+                    return Ok((mph, stats));
                 }
                 Err(MphError::Unresolvable) => continue,
                 Err(e) => return Err(e),
@@ -125,89 +386,269 @@ impl Builder {
     }
 }
 
-/// Single build attempt for a specific salt.
-fn try_build_once(keys: &[Vec<u8>], n: usize, salt: u64, cfg: &BuildConfig) -> Result<Mphf, MphError> {
+/// Bucket-size histogram of the first build round: `hist[s]` is the number of buckets
+/// that receive exactly `s` keys.
+///
+/// Only hashes the keys (no placement), so it is a cheap check of how well the hash
+/// spreads a key set and whether `target_bucket_size` should shrink. Keys are assumed
+/// unique and are not deduplicated; an empty set is `EmptyKeySet`, as for a build.
+pub fn bucket_histogram<K, I>(keys: I, cfg: &BuildConfig) -> Result<Vec<usize>, MphError>
+where
+    K: Borrow<[u8]>,
+    I: IntoIterator<Item = K>,
+{
+    cfg.validate()?;
+    let keys: Vec<K> = keys.into_iter().collect();
+    if keys.is_empty() {
+        return Err(MphError::EmptyKeySet);
+    }
+    let buckets_cnt = bucket_count(keys.len(), cfg);
+    let salt = mix_salt(cfg.bucket_salt.unwrap_or(cfg.salt), 0);
+    let mut sizes = vec![0usize; buckets_cnt];
+    for k in &keys {
+        sizes[KeyHash::from_key(k.borrow(), salt).bucket(buckets_cnt as u64)] += 1;
+    }
+    let mut hist = vec![0usize; sizes.iter().copied().max().unwrap_or(0) + 1];
+    for s in sizes {
+        hist[s] += 1;
+    }
+    Ok(hist)
+}
+
+/// Number of buckets for `n` keys: `cfg.buckets` if set, else the configured average
+/// bucket size.
+fn bucket_count(n: usize, cfg: &BuildConfig) -> usize {
+    match cfg.buckets {
+        Some(b) => b as usize,
+        None => ((n as f64 / cfg.target_bucket_size).ceil() as usize).max(1),
+    }
+}
+
+/// Per-round buffers, allocated once per build and cleared between salt rounds.
+///
+/// The `KeyHash`es themselves are not carried over: the salt seeds xxh3 directly, and no
+/// cheap transform maps hashes under one seed to hashes under another, so every round
+/// has to rehash the keys. Only the allocations are reused.
+struct Scratch {
+    buckets: Vec<Vec<KeyHash>>,
+    order: Vec<usize>,
+    occupied: BitSet,
+    disps: Vec<u64>,
+}
+
+impl Scratch {
+    fn new(n: usize, buckets_cnt: usize) -> Self {
+        Self {
+            buckets: vec![Vec::new(); buckets_cnt],
+            order: Vec::with_capacity(buckets_cnt),
+            occupied: BitSet::new(n),
+            disps: vec![0u64; buckets_cnt],
+        }
+    }
+
+    fn reset(&mut self) {
+        for b in &mut self.buckets {
+            b.clear();
+        }
+        self.order.clear();
+        self.occupied.clear();
+        self.disps.fill(0);
+    }
+}
+
+/// Single build attempt for the round's `(bucket salt, placement salt)`, which are
+/// equal unless `cfg.bucket_salt` is set. On success the raw (unpacked)
+/// displacements are left in `scratch.disps`.
+fn try_build_once(
+    keys: &[Vec<u8>],
+    n: usize,
+    (bucket_salt, salt): (u64, u64),
+    cfg: &BuildConfig,
+    scratch: &mut Scratch,
+) -> Result<(), MphError> {
     let n_u64 = n as u64;
+    scratch.reset();
+    let Scratch {
+        buckets,
+        order,
+        occupied,
+        disps,
+    } = scratch;
 
     // 1) Pre-hashing and bucketing.
-    let buckets_cnt = ((n as f64 / cfg.target_bucket_size).ceil() as usize).max(1);
-    let mut buckets: Vec<Vec<KeyHash>> = vec![Vec::new(); buckets_cnt];
+    let buckets_cnt = buckets.len();
     for k in keys {
-        let kh = KeyHash::from_key(k, salt);
+        let kh = KeyHash::from_key_split(k, bucket_salt, salt);
         let b = kh.bucket(buckets_cnt as u64);
         buckets[b].push(kh);
     }
 
     // 2) Process buckets by decreasing size (smaller buckets are easier to place later).
-    let mut order: Vec<usize> = (0..buckets_cnt).collect();
-    order.sort_by_key(|&b| -(buckets[b].len() as isize));
+    //    Equal sizes go by bucket index, so the order never depends on the sort algorithm.
+    order.extend(0..buckets_cnt);
+    order.sort_unstable_by_key(|&b| (Reverse(buckets[b].len()), b));
 
-    // 3) Global occupancy and per-bucket displacements.
-    let mut occupied = BitSet::new(n);
-    let mut disps = vec![0u64; buckets_cnt];
+    // 3) Global occupancy and per-bucket displacements live in `scratch`.
+
+    // 4) Place buckets.
+    #[cfg(feature = "parallel")]
+    if cfg.parallel_placement {
+        return place_parallel(buckets, order, occupied, disps, n_u64, salt, cfg);
+    }
 
     // Simple PRNG for selecting the next displacement.
-    let mut prng = XorShift64::seeded(0x9E37_79B9_7F4A_7C15 ^ salt);
+    let mut prng = XorShift64::seeded(cfg.prng_seed.unwrap_or(0x9E37_79B9_7F4A_7C15 ^ salt));
 
-    // 4) Place buckets.
-    for &b in &order {
-        let items = &buckets[b];
-        if items.is_empty() {
-            disps[b] = 0;
-            continue;
+    let mut free = n_u64;
+    for &b in order.iter() {
+        let (d, positions) =
+            find_disp(&buckets[b], occupied, n_u64, free, cfg, &mut prng)
+                .ok_or(MphError::Unresolvable)?;
+        // Success — mark slots.
+        free -= positions.len() as u64;
+        for p in positions {
+            occupied.set(p);
         }
+        disps[b] = d;
+    }
 
-        // Enumerate displacements (including 0), order is driven by the PRNG (but deterministic via salt).
-        let mut attempts = 0u32;
-        'find_disp: loop {
-            if attempts >= cfg.max_seed_attempts {
-                return Err(MphError::Unresolvable);
-            }
-            attempts += 1;
-
-            // Mixed strategy for robustness: some attempts use small displacements,
-            // others use pseudo-random values from the PRNG.
-            let d = if attempts <= 256 {
-                (attempts as u64 - 1) // 0,1,2,...,255 — cheap linear scan
-            } else {
-                prng.next()
-            };
-
-            // Check positions.
-            let mut ok = true;
-            let mut positions = Vec::with_capacity(items.len());
-            for kh in items {
-                let p = kh.place(n_u64, d);
-                if occupied.test(p) {
-                    ok = false;
-                    break;
-                }
-                positions.push(p);
-            }
-            if !ok {
-                continue;
-            }
-            // Check for duplicates inside the bucket.
-            positions.sort_unstable();
-            if positions.windows(2).any(|w| w[0] == w[1]) {
-                continue;
+    Ok(())
+}
+
+/// Mean of the minimal bit widths of `disps`.
+fn avg_disp_bits(disps: &[u64]) -> f64 {
+    if disps.is_empty() {
+        return 0.0;
+    }
+    let total: u64 = disps.iter().map(|&d| bit_width(d) as u64).sum();
+    total as f64 / disps.len() as f64
+}
+
+/// Displacements `0..LINEAR_DISPS` are scanned linearly before switching to the PRNG.
+const LINEAR_DISPS: u32 = 256;
+
+/// Upper bound on [`BuildConfig::attempt_scale`] caps: the number of distinct
+/// pseudo-random displacements.
+const MAX_SCALED_ATTEMPTS: u32 = 1 << RANDOM_DISP_BITS;
+
+/// Width of the pseudo-random displacements. Keeps the packed `disps` narrow while
+/// leaving far more candidates than `max_seed_attempts` can ever draw, and as many as
+/// an `attempt_scale` cap may.
+const RANDOM_DISP_BITS: u32 = 24;
+
+/// Keys whose slots are tested together by `BitSet::any_set` in `find_disp`: two AVX2
+/// gathers, while a collision in the first keys still skips most of a large bucket.
+const OCCUPANCY_CHUNK: usize = 8;
+
+/// Search a displacement placing every key of `items` on a free, distinct slot, with
+/// `free` of the `n` slots open. Returns the displacement and the slots it occupies, or
+/// `None` after `cfg.attempt_limit(..)`.
+fn find_disp(
+    items: &[KeyHash],
+    occupied: &BitSet,
+    n: u64,
+    free: u64,
+    cfg: &BuildConfig,
+    prng: &mut XorShift64,
+) -> Option<(u64, Vec<usize>)> {
+    if items.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let max_attempts = cfg.attempt_limit(items.len(), free, n);
+    let avx2 = Avx2::detect();
+
+    // Enumerate displacements (including 0), order is driven by the PRNG (but deterministic via salt).
+    let mut positions = Vec::with_capacity(items.len());
+    for attempts in 1..=max_attempts {
+        // Mixed strategy for robustness: some attempts use small displacements,
+        // others use pseudo-random values from the PRNG (bounded so they pack narrowly).
+        let d = if attempts <= LINEAR_DISPS {
+            attempts as u64 - 1 // 0,1,2,...,255 — cheap linear scan
+        } else {
+            prng.next_u64() >> (64 - RANDOM_DISP_BITS)
+        };
+
+        // Check positions, a chunk at a time so a collision still stops the scan early.
+        positions.clear();
+        let mut ok = true;
+        for chunk in items.chunks(OCCUPANCY_CHUNK) {
+            let start = positions.len();
+            positions.extend(chunk.iter().map(|kh| kh.place_with(cfg.placement, n, d)));
+            if occupied.any_set(&positions[start..], avx2) {
+                ok = false;
+                break;
             }
+        }
+        if !ok {
+            continue;
+        }
+        // Check for duplicates inside the bucket.
+        positions.sort_unstable();
+        if positions.windows(2).any(|w| w[0] == w[1]) {
+            continue;
+        }
+        return Some((d, positions));
+    }
+    None
+}
+
+/// Buckets searched concurrently per batch. Fixed (not tied to the thread count) so the
+/// result does not depend on the machine.
+#[cfg(feature = "parallel")]
+const PARALLEL_BATCH: usize = 64;
 
-            // Success — mark slots.
+/// Parallel placement: every bucket of a batch searches against the same occupancy
+/// snapshot with its own PRNG; results are then committed in `order`. A bucket whose
+/// slots were taken by an earlier commit of the same batch searches again sequentially.
+#[cfg(feature = "parallel")]
+fn place_parallel(
+    buckets: &[Vec<KeyHash>],
+    order: &[usize],
+    occupied: &mut BitSet,
+    disps: &mut [u64],
+    n: u64,
+    salt: u64,
+    cfg: &BuildConfig,
+) -> Result<(), MphError> {
+    use rayon::prelude::*;
+
+    let seed = cfg.prng_seed.unwrap_or(salt);
+    let bucket_prng = |b: usize| XorShift64::seeded(splitmix64(seed ^ b as u64));
+
+    let mut free = n;
+    for batch in order.chunks(PARALLEL_BATCH) {
+        let snapshot = &*occupied;
+        let found: Vec<Option<(u64, Vec<usize>)>> = batch
+            .par_iter()
+            .map(|&b| find_disp(&buckets[b], snapshot, n, free, cfg, &mut bucket_prng(b)))
+            .collect();
+
+        for (&b, res) in batch.iter().zip(found) {
+            // Failing against the snapshot means failing against any superset of it.
+            let (mut d, mut positions) = res.ok_or(MphError::Unresolvable)?;
+            if positions.iter().any(|&p| occupied.test(p)) {
+                (d, positions) =
+                    find_disp(&buckets[b], occupied, n, free, cfg, &mut bucket_prng(b))
+                        .ok_or(MphError::Unresolvable)?;
+            }
+            free -= positions.len() as u64;
             for p in positions {
                 occupied.set(p);
             }
             disps[b] = d;
-            break 'find_disp;
         }
     }
+    Ok(())
+}
 
-    Ok(Mphf {
-        n: n_u64,
-        buckets: buckets_cnt as u64,
-        salt,
-        disps,
-    })
+#[cfg(feature = "parallel")]
+#[inline]
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
 /// Minimal xorshift PRNG.
@@ -218,7 +659,7 @@ impl XorShift64 {
         Self(s)
     }
     #[inline]
-    fn next(&mut self) -> u64 {
+    fn next_u64(&mut self) -> u64 {
         let mut x = self.0;
         x ^= x << 13;
         x ^= x >> 7;
@@ -228,12 +669,14 @@ impl XorShift64 {
     }
 }
 
-/// Deterministically mix the base salt with the round number.
+/// Deterministically mix the base salt with the round number. The round is spread
+/// over all 64 bits first, so nearby bases (0 and 1, say) don't retry each other's
+/// salts; round 0 is the plain base mix.
 fn mix_salt(base: u64, round: u32) -> u64 {
     const FNV_OFFSET: u64 = 0xcbf29ce484222325;
     const FNV_PRIME:  u64 = 0x100000001b3;
     let mut h = FNV_OFFSET ^ base;
-    h ^= round as u64;
+    h ^= (round as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
     h = h.wrapping_mul(FNV_PRIME);
     h ^ (h >> 33)
 }
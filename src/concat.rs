@@ -0,0 +1,98 @@
+use crate::bdz::{MphError, Mphf, wyhash1};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Seed of the top-level hash that routes a key to its part.
+const PARTITION_SEED: u64 = 0x3C6E_F372_FE94_F82B;
+
+/// Part a key belongs to among `parts` shards. Shard keys with this before building
+/// the parts passed to [`Mphf::concat`], so lookups can be routed back.
+#[inline]
+pub fn partition_of(key: &[u8], parts: usize) -> usize {
+    // Multiply-high reduction: uniform over `0..parts` without a division.
+    ((wyhash1(key, PARTITION_SEED) as u128 * parts as u128) >> 64) as usize
+}
+
+/// Several MPHs combined into one global numbering: part `p` owns the indices
+/// `offsets[p]..offsets[p + 1]`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct ConcatMphf {
+    parts: Vec<Mphf>,
+    offsets: Vec<u64>, // len == parts.len() + 1, offsets[0] == 0
+}
+
+impl Mphf {
+    /// Combine independently built parts into a single index space `[0, total_n)`
+    /// (the sum of the parts' [`range`](Mphf::range)s if some are not minimal).
+    ///
+    /// Preconditions (not checked): the parts' key sets are disjoint, and every key of
+    /// part `p` satisfies `partition_of(key, parts.len()) == p`, which is how
+    /// [`ConcatMphf::index`] finds the part. Parts sharded by some other rule can still
+    /// be queried with [`ConcatMphf::index_in`].
+    pub fn concat(parts: &[Mphf]) -> Result<ConcatMphf, MphError> {
+        if parts.is_empty() {
+            return Err(MphError::InvalidConfig(
+                "concat needs at least one part".into(),
+            ));
+        }
+        ConcatMphf::from_parts(parts.to_vec())
+    }
+}
+
+impl ConcatMphf {
+    /// [`Mphf::concat`] taking ownership of the (non-empty) parts.
+    pub(crate) fn from_parts(parts: Vec<Mphf>) -> Result<Self, MphError> {
+        let mut offsets = Vec::with_capacity(parts.len() + 1);
+        let mut total = 0u64;
+        offsets.push(total);
+        for p in &parts {
+            total = total.checked_add(p.range()).ok_or_else(|| {
+                MphError::InvalidConfig("total index range of the parts overflows u64".into())
+            })?;
+            offsets.push(total);
+        }
+        Ok(ConcatMphf { parts, offsets })
+    }
+
+    /// Global index of `key`, routed to its part by [`partition_of`].
+    #[inline]
+    pub fn index(&self, key: &[u8]) -> u64 {
+        self.index_in(partition_of(key, self.parts.len()), key)
+    }
+
+    #[inline]
+    pub fn index_str(&self, s: &str) -> u64 {
+        self.index(s.as_bytes())
+    }
+
+    /// Global index of `key` when its part is already known.
+    #[inline]
+    pub fn index_in(&self, part: usize, key: &[u8]) -> u64 {
+        self.offsets[part] + self.parts[part].index(key)
+    }
+
+    /// Total number of keys across all parts.
+    #[inline]
+    pub fn n(&self) -> u64 {
+        self.parts.iter().map(|p| p.n).sum()
+    }
+
+    /// Exclusive upper bound of `index`: the sum of the parts'
+    /// [`range`](Mphf::range)s, which is [`n`](Self::n) when every part is minimal.
+    #[inline]
+    pub fn range(&self) -> u64 {
+        self.offsets[self.parts.len()]
+    }
+
+    #[inline]
+    pub fn parts(&self) -> &[Mphf] {
+        &self.parts
+    }
+
+    /// First global index of each part, plus the total as the last element.
+    #[inline]
+    pub fn offsets(&self) -> &[u64] {
+        &self.offsets
+    }
+}
@@ -0,0 +1,94 @@
+use crate::bdz::{BuildConfig, Builder, MphError, Mphf};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Read-only map: MPH + one value per slot, stored in index order.
+///
+/// There is no membership check: `get` on a key outside the build set returns some
+/// other key's value. Pair it with a [`FrozenSet`](crate::FrozenSet) if that matters.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct FrozenMap<V> {
+    mph: Mphf,
+    values: Vec<V>, // values[mph.index(k)] == payload of k
+}
+
+impl<V> FrozenMap<V> {
+    /// Build over **unique** keys, computing each key's value with `payload` and
+    /// storing it straight at the key's index (no second lookup pass). An empty `keys`
+    /// fails with `EmptyKeySet`.
+    pub fn build_with<K, F>(keys: &[K], mut payload: F) -> Result<Self, MphError>
+    where
+        K: AsRef<[u8]>,
+        F: FnMut(&[u8]) -> V,
+    {
+        if keys.is_empty() {
+            return Err(MphError::EmptyKeySet);
+        }
+        let mph = Builder::new().build(keys.iter().map(|k| k.as_ref()))?;
+        let mut slots: Vec<Option<V>> = std::iter::repeat_with(|| None).take(keys.len()).collect();
+        for k in keys {
+            let k = k.as_ref();
+            slots[mph.index(k) as usize] = Some(payload(k));
+        }
+        // Every slot is filled: the keys are unique and `mph` is a bijection onto [0, n).
+        let values = slots.into_iter().map(Option::unwrap).collect();
+        Ok(Self { mph, values })
+    }
+
+    /// Build under `cfg` from values given in key order (`values[i]` belongs to `keys[i]`).
+    pub(crate) fn build_ordered<K: AsRef<[u8]>>(
+        keys: &[K],
+        values: Vec<V>,
+        cfg: BuildConfig,
+    ) -> Result<Self, MphError> {
+        assert_eq!(keys.len(), values.len(), "one value per key");
+        if keys.is_empty() {
+            return Err(MphError::EmptyKeySet);
+        }
+        let mph = Builder::new()
+            .with_config(cfg)
+            .build(keys.iter().map(|k| k.as_ref()))?;
+        let mut slots: Vec<Option<V>> = std::iter::repeat_with(|| None).take(keys.len()).collect();
+        for (k, v) in keys.iter().zip(values) {
+            slots[mph.index(k.as_ref()) as usize] = Some(v);
+        }
+        let values = slots.into_iter().map(Option::unwrap).collect();
+        Ok(Self { mph, values })
+    }
+
+    /// Value of `key`; arbitrary (but in-bounds) for keys outside the build set.
+    #[inline]
+    pub fn get(&self, key: &[u8]) -> &V {
+        &self.values[self.mph.index(key) as usize]
+    }
+
+    #[inline]
+    pub fn get_str(&self, s: &str) -> &V {
+        self.get(s.as_bytes())
+    }
+
+    /// Number of keys in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Always false: a map is never built over no keys (see [`FrozenMap::build_with`]).
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Values in index order: `values()[mph.index(k)]` belongs to `k`.
+    #[inline]
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+
+    /// The underlying MPH.
+    #[inline]
+    pub fn mphf(&self) -> &Mphf {
+        &self.mph
+    }
+}
@@ -0,0 +1,85 @@
+use crate::bdz::{Builder, FINGERPRINT_SEED, MphError, Mphf, wyhash1};
+use crate::util::PackedArray;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Read-only membership set: MPH + `b`-bit fingerprint per slot ("perfect hash filter").
+///
+/// `contains` never yields false negatives; a non-member is accepted with
+/// probability `2^-b`, using `b` bits per key on top of the MPH itself.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct FrozenSet {
+    mph: Mphf,
+    fingerprints: PackedArray, // len == n, `b` bits each
+}
+
+impl FrozenSet {
+    /// Build over **unique** keys with `fingerprint_bits` (1..=64) bits per key.
+    /// An empty `keys` fails with `EmptyKeySet`.
+    pub fn build<K: AsRef<[u8]>>(keys: &[K], fingerprint_bits: u8) -> Result<Self, MphError> {
+        if keys.is_empty() {
+            return Err(MphError::EmptyKeySet);
+        }
+        let mph = Builder::new().build(keys.iter().map(|k| k.as_ref()))?;
+        Ok(Self::from_mphf(mph, keys, fingerprint_bits))
+    }
+
+    /// Attach fingerprints to an MPH already built over exactly `keys`.
+    pub fn from_mphf<K: AsRef<[u8]>>(mph: Mphf, keys: &[K], fingerprint_bits: u8) -> Self {
+        assert!(
+            (1..=64).contains(&fingerprint_bits),
+            "fingerprint_bits must be in 1..=64"
+        );
+        assert_eq!(keys.len() as u64, mph.n, "keys must be the MPH's build set");
+        assert!(mph.is_minimal(), "FrozenSet needs a minimal MPH");
+        let mut fingerprints = PackedArray::new(keys.len(), fingerprint_bits);
+        for k in keys {
+            let k = k.as_ref();
+            let fp = fingerprint(k, mph.salt, fingerprint_bits);
+            fingerprints.set(mph.index(k) as usize, fp);
+        }
+        Self { mph, fingerprints }
+    }
+
+    #[inline]
+    pub fn contains(&self, key: &[u8]) -> bool {
+        let i = self.mph.index(key) as usize;
+        self.fingerprints.get(i) == fingerprint(key, self.mph.salt, self.fingerprints.width())
+    }
+
+    #[inline]
+    pub fn contains_str(&self, s: &str) -> bool {
+        self.contains(s.as_bytes())
+    }
+
+    /// Number of keys in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.mph.n as usize
+    }
+
+    /// Always false: a set is never built over no keys (see [`FrozenSet::build`]).
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.mph.n == 0
+    }
+
+    /// Bits stored per key for the fingerprint (false-positive rate `2^-bits`).
+    #[inline]
+    pub fn fingerprint_bits(&self) -> u8 {
+        self.fingerprints.width()
+    }
+
+    /// The underlying MPH.
+    #[inline]
+    pub fn mphf(&self) -> &Mphf {
+        &self.mph
+    }
+}
+
+/// Low `bits` (in `1..=64`) of the key's fingerprint hash.
+#[inline]
+fn fingerprint(key: &[u8], salt: u64, bits: u8) -> u64 {
+    wyhash1(key, salt ^ FINGERPRINT_SEED) & (u64::MAX >> (64 - bits as u32))
+}
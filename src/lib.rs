@@ -3,6 +3,71 @@
 //! - Build once on a set of **unique** keys (bytes/str).
 //! - O(1) lookups: key -> unique index in `[0..n)`.
 //! - Robust: if a build attempt finds a cycle, we rehash with another salt.
+//! - `checked` feature: every internal unchecked access becomes bounds-checked,
+//!   turning violated invariants into panics for Miri and fuzzing.
+
+// Vertex ids are `u32` and used directly as slice indices.
+#[cfg(target_pointer_width = "16")]
+compile_error!("minimal_perfect_hash requires a 32- or 64-bit target");
 
 mod bdz;
-pub use bdz::{BuildConfig, Builder, MphError, Mphf};
+#[cfg(feature = "binary")]
+mod binary;
+mod builder;
+mod concat;
+mod frozen_map;
+mod frozen_set;
+mod hash;
+#[cfg(feature = "binary")]
+mod repr_c;
+mod static_map;
+mod streaming;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "debug-trace")]
+mod trace;
+pub mod util;
+mod wyhash_fixed;
+pub use bdz::{
+    BuildConfig, BuildStats, Builder, DegreeStats, FailedBuild, HASH_VERSION, IndexCollision,
+    MAX_KEYS, MIN_GAMMA_RANK3, MphError, Mphf, SaltSchedule, ValidationReport,
+    estimate_build_memory, mph_fingerprint, mph_hash, prehash, vertex_triple,
+};
+pub use concat::{ConcatMphf, partition_of};
+pub use frozen_map::FrozenMap;
+pub use frozen_set::FrozenSet;
+#[cfg(feature = "binary")]
+pub use repr_c::MphfHeader;
+pub use static_map::StaticMap;
+pub use streaming::StreamingBuilder;
+#[cfg(feature = "debug-trace")]
+pub use trace::{BdzTrace, PeelStep, try_build_debug};
+
+/// Whether this build of the crate includes the "parallel" feature, which may have
+/// been enabled by another crate in the dependency graph. With it, BDZ builds hash,
+/// deduplicate and derive vertices on the rayon pool.
+pub fn parallel_enabled() -> bool {
+    cfg!(feature = "parallel")
+}
+
+/// Whether the AVX2 paths run here: the "simd" feature is compiled in (without
+/// "checked"), the target is x86_64 and this CPU supports AVX2. Only the CHD
+/// builder's displacement search has such a path.
+pub fn simd_enabled() -> bool {
+    #[cfg(all(feature = "simd", target_arch = "x86_64", not(feature = "checked")))]
+    {
+        std::arch::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64", not(feature = "checked"))))]
+    {
+        false
+    }
+}
+
+/// CHD-style MPH (hash-and-displace with per-bucket displacements).
+pub mod chd {
+    pub use crate::builder::{
+        BuildConfig, BuildStats, Builder, MphError, Mphf, SpaceStats, bucket_histogram,
+    };
+    pub use crate::hash::{KeyHash, Placement};
+}
@@ -0,0 +1,164 @@
+//! Flat `#[repr(C)]` layout of a BDZ [`Mphf`] for zero-copy loading (`binary`
+//! feature): a fixed 40-byte [`MphfHeader`] followed directly by `g`, so an mmapped
+//! file can be cast in place instead of parsed.
+//!
+//! Layout (all integers little-endian, no implicit padding):
+//!
+//! | offset | field                      | size      |
+//! |--------|----------------------------|-----------|
+//! | 0      | magic `MPHC`               | 4         |
+//! | 4      | version (= `HASH_VERSION`) | 4         |
+//! | 8      | `n`                        | 8         |
+//! | 16     | `m`                        | 4         |
+//! | 20     | flags                      | 4         |
+//! | 24     | `salt`                     | 8         |
+//! | 32     | `g_len` (= `m`)            | 8         |
+//! | 40     | `g`                        | 4 × `m`   |
+//!
+//! Flag bit 0 is set for minimal builds; the other bits must be zero. The blob is
+//! exactly `40 + 4 * m` bytes. Fingerprints and a remap table have no place in it.
+//! Sets below 32 keys are stored with `m == n`: `g` then holds their sorted key
+//! hashes rather than vertex values.
+
+use crate::bdz::{HASH_VERSION, MphError, Mphf, MphfParts, check_shape};
+
+const MAGIC: [u8; 4] = *b"MPHC";
+const FLAG_MINIMAL: u32 = 1;
+
+/// Header of the [`to_bytes_repr_c`](Mphf::to_bytes_repr_c) layout, castable from
+/// the first 40 bytes of a blob (e.g. with `bytemuck`, or [`MphfHeader::view`]).
+///
+/// Fields are little-endian and laid out in declaration order with no padding; the
+/// header is followed directly by `g_len` (= `m`) little-endian u32s of `g`. Sets
+/// below 32 keys are stored with `m == n`, and their `g` holds sorted key hashes
+/// rather than vertex values.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MphfHeader {
+    pub magic: [u8; 4],
+    pub version: u32,
+    pub n: u64,
+    pub m: u32,
+    pub flags: u32,
+    pub salt: u64,
+    pub g_len: u64,
+}
+
+const HEADER_LEN: usize = size_of::<MphfHeader>();
+const _: () = assert!(HEADER_LEN == 40 && align_of::<MphfHeader>() == 8);
+
+impl MphfHeader {
+    /// Borrow the header and `g` of a blob in place, without copying. `bytes` must
+    /// start at an 8-byte aligned address (an mmap always does) and the target must be
+    /// little-endian; both are checked, as is everything
+    /// [`from_bytes_repr_c`](Mphf::from_bytes_repr_c) checks.
+    pub fn view(bytes: &[u8]) -> Result<(&MphfHeader, &[u32]), MphError> {
+        if cfg!(target_endian = "big") {
+            return Err(MphError::Binary(
+                "in-place view needs a little-endian target",
+            ));
+        }
+        if !(bytes.as_ptr() as usize).is_multiple_of(align_of::<MphfHeader>()) {
+            return Err(MphError::Binary("blob is not 8-byte aligned"));
+        }
+        let header = read_header(bytes)?;
+        let m = header.m as usize;
+        // Safety: `read_header` checked that `bytes` holds 40 header bytes followed by
+        // exactly `m` u32s; the start is 8-aligned, so the header (align 8) and `g`
+        // (offset 40, align 4) are aligned; every bit pattern is a valid value of
+        // these integer fields, and on a little-endian target the in-memory values
+        // are the ones `read_header` validated.
+        unsafe {
+            let h = &*(bytes.as_ptr() as *const MphfHeader);
+            let g = std::slice::from_raw_parts(bytes.as_ptr().add(HEADER_LEN) as *const u32, m);
+            Ok((h, g))
+        }
+    }
+}
+
+impl Mphf {
+    /// Encode as an [`MphfHeader`] followed by `g`. Instances carrying fingerprints or
+    /// a remap table are rejected with `MphError::Binary`, as the layout has no room
+    /// for them.
+    pub fn to_bytes_repr_c(&self) -> Result<Vec<u8>, MphError> {
+        if self.fingerprints.is_some() || self.remap.is_some() {
+            return Err(MphError::Binary(
+                "repr(C) layout holds neither fingerprints nor a remap",
+            ));
+        }
+        let mut out = Vec::with_capacity(HEADER_LEN + 4 * self.g.len());
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&HASH_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.n.to_le_bytes());
+        out.extend_from_slice(&self.m.to_le_bytes());
+        let flags = if self.minimal { FLAG_MINIMAL } else { 0 };
+        out.extend_from_slice(&flags.to_le_bytes());
+        out.extend_from_slice(&self.salt.to_le_bytes());
+        out.extend_from_slice(&(self.g.len() as u64).to_le_bytes());
+        for &v in &self.g {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Ok(out)
+    }
+
+    /// Decode bytes written by [`to_bytes_repr_c`](Self::to_bytes_repr_c) into an
+    /// owned `Mphf`. Any alignment and endianness; use [`MphfHeader::view`] to borrow
+    /// the blob instead. Magic, version, flags, `n <= m` (with `m == n` only below 32
+    /// keys), `g_len == m` and the total length are checked, so the result is safe to
+    /// query. A blob stamped with another [`HASH_VERSION`] fails with
+    /// [`MphError::UnsupportedHashVersion`].
+    pub fn from_bytes_repr_c(bytes: &[u8]) -> Result<Self, MphError> {
+        let header = read_header(bytes)?;
+        let g = bytes[HEADER_LEN..]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().expect("4-byte chunk")))
+            .collect();
+        Ok(MphfParts {
+            n: header.n,
+            m: header.m,
+            salt: header.salt,
+            g,
+            fingerprints: None,
+            minimal: header.flags & FLAG_MINIMAL != 0,
+            remap: None,
+        }
+        .into())
+    }
+}
+
+/// Decode and validate the header of a blob of exactly `40 + 4 * m` bytes.
+fn read_header(bytes: &[u8]) -> Result<MphfHeader, MphError> {
+    let field = |at: usize, len: usize| &bytes[at..at + len];
+    let u32_at = |at| u32::from_le_bytes(field(at, 4).try_into().unwrap());
+    let u64_at = |at| u64::from_le_bytes(field(at, 8).try_into().unwrap());
+    if bytes.len() < HEADER_LEN {
+        return Err(MphError::Binary("truncated"));
+    }
+    let header = MphfHeader {
+        magic: field(0, 4).try_into().unwrap(),
+        version: u32_at(4),
+        n: u64_at(8),
+        m: u32_at(16),
+        flags: u32_at(20),
+        salt: u64_at(24),
+        g_len: u64_at(32),
+    };
+    if header.magic != MAGIC {
+        return Err(MphError::Binary("bad magic"));
+    }
+    if header.version != HASH_VERSION {
+        let version = u8::try_from(header.version).unwrap_or(u8::MAX);
+        return Err(MphError::UnsupportedHashVersion(version));
+    }
+    if header.flags & !FLAG_MINIMAL != 0 {
+        return Err(MphError::Binary("unknown flags"));
+    }
+    check_shape(header.n, header.m).map_err(MphError::Binary)?;
+    if header.g_len != header.m as u64 {
+        return Err(MphError::Binary("g_len must equal m"));
+    }
+    if (bytes.len() - HEADER_LEN) as u64 != 4 * header.g_len {
+        return Err(MphError::Binary("length does not match g_len"));
+    }
+    Ok(header)
+}
@@ -0,0 +1,169 @@
+use crate::bdz::{BuildConfig, MphError};
+use crate::frozen_map::FrozenMap;
+use crate::util::{FrontCoded, PackedArray, bit_width};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Read-only `HashMap<Vec<u8>, V>` replacement with exact lookups.
+///
+/// A [`FrozenMap`] with 1-byte fingerprints, plus a copy of every key so `get` can
+/// confirm a match: a non-member is rejected by the fingerprint ~255/256 of the time,
+/// and by comparing against the stored key otherwise. Keys cost their bytes plus 8
+/// bytes each, or much less with [`new_front_coded`](Self::new_front_coded) when they
+/// share prefixes. Use [`FrozenMap`] or the bare [`Mphf`](crate::Mphf) when the keys
+/// are known to be members and that copy is not worth it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct StaticMap<V> {
+    map: FrozenMap<V>,
+    keys: Keys,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+enum Keys {
+    /// All keys concatenated in index order: key i is `blob[ends[i - 1]..ends[i]]`
+    /// (start 0 for i = 0).
+    Plain { blob: Vec<u8>, ends: Vec<usize> },
+    /// Keys front-coded in sorted order: key i is `sorted.get(ranks[i])`.
+    FrontCoded {
+        sorted: FrontCoded,
+        ranks: PackedArray,
+    },
+}
+
+impl<V> StaticMap<V> {
+    /// Build from `(key, value)` pairs with unique keys (`DuplicateKey` otherwise).
+    /// An empty input fails with `EmptyKeySet`.
+    pub fn new<K, I>(entries: I) -> Result<Self, MphError>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let (keys, map) = Self::build(entries)?;
+        let mut order = vec![0usize; keys.len()];
+        for (pos, k) in keys.iter().enumerate() {
+            order[map.mphf().index(k.as_ref()) as usize] = pos;
+        }
+        let total = keys.iter().map(|k| k.as_ref().len()).sum();
+        let mut blob = Vec::with_capacity(total);
+        let mut ends = Vec::with_capacity(keys.len());
+        for pos in order {
+            blob.extend_from_slice(keys[pos].as_ref());
+            ends.push(blob.len());
+        }
+        Ok(Self {
+            map,
+            keys: Keys::Plain { blob, ends },
+        })
+    }
+
+    /// [`new`](Self::new), but the keys are kept sorted and [`FrontCoded`], plus a
+    /// bit-packed index → rank table of `log2(n)` bits per key. For sorted
+    /// vocabularies with long shared prefixes (paths, URLs, dictionary words) that is
+    /// a fraction of the plain copy; see [`key_bytes`](Self::key_bytes). Entries may
+    /// come in any order, but are sorted by key during the build.
+    ///
+    /// Confirming a match then decodes up to 16 keys of a bucket and allocates, so
+    /// `get` and [`get_key`](Self::get_key) are slower than on a plain map.
+    pub fn new_front_coded<K, I>(entries: I) -> Result<Self, MphError>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let (keys, map) = Self::build(entries)?;
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_unstable_by(|&a, &b| keys[a].as_ref().cmp(keys[b].as_ref()));
+        let sorted = FrontCoded::new(order.iter().map(|&pos| keys[pos].as_ref()));
+        let mut ranks = PackedArray::new(keys.len(), bit_width(keys.len() as u64 - 1));
+        for (rank, &pos) in order.iter().enumerate() {
+            ranks.set(map.mphf().index(keys[pos].as_ref()) as usize, rank as u64);
+        }
+        Ok(Self {
+            map,
+            keys: Keys::FrontCoded { sorted, ranks },
+        })
+    }
+
+    fn build<K, I>(entries: I) -> Result<(Vec<K>, FrozenMap<V>), MphError>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let (keys, values): (Vec<K>, Vec<V>) = entries.into_iter().unzip();
+        if keys.is_empty() {
+            return Err(MphError::EmptyKeySet);
+        }
+        let cfg = BuildConfig {
+            fingerprint: true,
+            ..Default::default()
+        };
+        let map = FrozenMap::build_ordered(&keys, values, cfg)?;
+        Ok((keys, map))
+    }
+
+    /// Value of `key`, or `None` if it was not in the build set.
+    #[inline]
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        let i = self.map.mphf().index_if_member(key)? as usize;
+        (*self.get_key(i) == *key).then(|| &self.map.values()[i])
+    }
+
+    #[inline]
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Entries in index order (not insertion order).
+    pub fn iter(&self) -> impl Iterator<Item = (Cow<'_, [u8]>, &V)> {
+        (0..self.len()).map(|i| (self.get_key(i), &self.map.values()[i]))
+    }
+
+    /// Key at `index` (in `[0, len())`, the order of [`iter`](Self::iter)): borrowed
+    /// from a plain map, decoded from a front-coded one.
+    pub fn get_key(&self, index: usize) -> Cow<'_, [u8]> {
+        match &self.keys {
+            Keys::Plain { blob, ends } => {
+                let start = if index == 0 { 0 } else { ends[index - 1] };
+                Cow::Borrowed(&blob[start..ends[index]])
+            }
+            Keys::FrontCoded { sorted, ranks } => Cow::Owned(sorted.get(ranks.get(index) as usize)),
+        }
+    }
+
+    /// Heap bytes spent on the retained keys (and, when front-coded, the rank table).
+    pub fn key_bytes(&self) -> usize {
+        match &self.keys {
+            Keys::Plain { blob, ends } => blob.len() + ends.len() * size_of::<usize>(),
+            Keys::FrontCoded { sorted, ranks } => sorted.size_bytes() + ranks.size_bytes(),
+        }
+    }
+
+    /// The underlying map.
+    #[inline]
+    pub fn frozen_map(&self) -> &FrozenMap<V> {
+        &self.map
+    }
+}
+
+impl<V> TryFrom<HashMap<Vec<u8>, V>> for StaticMap<V> {
+    type Error = MphError;
+
+    /// Freeze an existing map; an empty one fails with `EmptyKeySet`.
+    fn try_from(map: HashMap<Vec<u8>, V>) -> Result<Self, Self::Error> {
+        Self::new(map)
+    }
+}
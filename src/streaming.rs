@@ -0,0 +1,123 @@
+use crate::bdz::{BuildConfig, Builder, MphError, Mphf, wyhash1};
+use hashbrown::HashTable;
+
+/// Seed of the hash used for incremental duplicate detection.
+const DEDUP_SEED: u64 = 0x510E_527F_ADE6_82D1;
+
+/// Stateful BDZ builder fed one key at a time, e.g. from a channel.
+///
+/// Keys are appended to a single byte blob, so each `push` costs the key bytes plus
+/// about 26 bytes of bookkeeping: an 8-byte end offset and a 16-byte `(hash, index)`
+/// dedup entry with its control byte at hashbrown's 7/8 load (amortized growth of the
+/// vectors can transiently double that). Raw keys, not hashes, are kept because a
+/// rehash round needs to hash them again under a new salt.
+pub struct StreamingBuilder {
+    cfg: BuildConfig,
+    keys: KeyArena,
+}
+
+impl Default for StreamingBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingBuilder {
+    pub fn new() -> Self {
+        Self {
+            cfg: BuildConfig::default(),
+            keys: KeyArena::default(),
+        }
+    }
+
+    pub fn with_config(mut self, cfg: BuildConfig) -> Self {
+        self.cfg = cfg;
+        self
+    }
+
+    /// Append a key. Fails with `DuplicateKey` (leaving the builder unchanged) if the
+    /// exact same bytes were pushed before.
+    pub fn push(&mut self, key: &[u8]) -> Result<(), MphError> {
+        self.keys.push(key)
+    }
+
+    /// Number of keys pushed so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.len() == 0
+    }
+
+    /// Build the MPH over every pushed key. Fails with `EmptyKeySet` if nothing was
+    /// pushed.
+    pub fn finish(self) -> Result<Mphf, MphError> {
+        let Self { cfg, mut keys } = self;
+        if keys.len() == 0 {
+            return Err(MphError::EmptyKeySet);
+        }
+        keys.release_dedup();
+        Builder::new()
+            .with_config(cfg)
+            .build_keys(keys.len(), |i| keys.get(i))
+            .map(|(mph, _)| mph)
+    }
+}
+
+/// Append-only store of unique keys: one byte blob plus end offsets, with exact
+/// duplicate detection that indexes into the blob instead of copying keys.
+#[derive(Default)]
+pub(crate) struct KeyArena {
+    blob: Vec<u8>,
+    ends: Vec<usize>,              // ends[i] == end of key i in `blob`
+    seen: HashTable<(u64, usize)>, // (dedup hash, key index)
+}
+
+impl KeyArena {
+    /// Append `key`, or fail with `DuplicateKey` (leaving the arena unchanged).
+    pub(crate) fn push(&mut self, key: &[u8]) -> Result<(), MphError> {
+        let Self { blob, ends, seen } = self;
+        let h = wyhash1(key, DEDUP_SEED);
+        if seen
+            .find(h, |&(_, i)| key_at(blob, ends, i) == key)
+            .is_some()
+        {
+            return Err(MphError::DuplicateKey);
+        }
+        blob.extend_from_slice(key);
+        ends.push(blob.len());
+        seen.insert_unique(h, (h, ends.len() - 1), |&(h, _)| h);
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, i: usize) -> &[u8] {
+        key_at(&self.blob, &self.ends, i)
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Forget every key, keeping all allocations.
+    pub(crate) fn clear(&mut self) {
+        self.blob.clear();
+        self.ends.clear();
+        self.seen.clear();
+    }
+
+    /// Free the dedup table once no more keys will be pushed.
+    fn release_dedup(&mut self) {
+        self.seen = HashTable::new();
+    }
+}
+
+#[inline]
+fn key_at<'a>(blob: &'a [u8], ends: &[usize], i: usize) -> &'a [u8] {
+    let start = if i == 0 { 0 } else { ends[i - 1] };
+    &blob[start..ends[i]]
+}
@@ -0,0 +1,39 @@
+//! Test helpers shared with downstream crates ("testing" feature).
+
+use crate::Mphf;
+use crate::util::BitSet;
+
+/// Panic unless `mph` maps `keys` one-to-one onto `[0, n)`.
+///
+/// `keys` must be the full build set. The message names the offending keys and
+/// indices, so it is usable directly in integration tests.
+pub fn assert_minimal_perfect<K: AsRef<[u8]>>(mph: &Mphf, keys: &[K]) {
+    assert_eq!(
+        keys.len() as u64,
+        mph.n,
+        "key count {} does not match the MPH's n = {}",
+        keys.len(),
+        mph.n
+    );
+    let n = keys.len();
+    let mut seen = BitSet::new(n);
+    let mut owner = vec![usize::MAX; n];
+    for (pos, k) in keys.iter().enumerate() {
+        let k = k.as_ref();
+        let i = mph.index(k);
+        assert!(
+            i < n as u64,
+            "key #{pos} {k:?} mapped to {i}, outside [0, {n})"
+        );
+        let i = i as usize;
+        if seen.test(i) {
+            let prev = owner[i];
+            panic!(
+                "collision at index {i}: key #{prev} {:?} and key #{pos} {k:?}",
+                keys[prev].as_ref()
+            );
+        }
+        seen.set(i);
+        owner[i] = pos;
+    }
+}
@@ -0,0 +1,91 @@
+//! Peeling trace of a single BDZ attempt, for debugging and visualizing failed
+//! builds (`debug-trace` feature). Builds never go through this module.
+
+use crate::bdz::{
+    BuildConfig, GraphScratch, MphError, build_from_edges, build_vertex_count, derive_vertices_with,
+};
+
+/// One peeling step: edge `edge` (the key at that position of the input) was removed
+/// through `vertex`, which had degree 1 at that point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeelStep {
+    pub edge: u32,
+    pub vertex: u32,
+}
+
+/// What [`try_build_debug`] observed.
+#[derive(Debug, Clone)]
+pub struct BdzTrace {
+    /// Effective salt of the attempt (round `cfg.salt_round_start` of `cfg.salt`).
+    pub salt: u64,
+    /// Vertex count of the hypergraph.
+    pub m: u32,
+    /// The three vertices of each key's edge, in input order.
+    pub edges: Vec<[u32; 3]>,
+    /// Edges in the order they were peeled.
+    pub peel_order: Vec<PeelStep>,
+    /// Degree of every vertex once peeling stopped, counting only unpeeled edges.
+    /// Vertices of the 2-core keep a degree of 2 or more; everything else ends at 0.
+    pub final_degrees: Vec<u32>,
+    /// Edges left when peeling stopped: the 2-core that made the attempt fail.
+    pub unpeeled: Vec<u32>,
+}
+
+impl BdzTrace {
+    /// Whether every edge peeled, i.e. the attempt would have succeeded.
+    pub fn is_peelable(&self) -> bool {
+        self.unpeeled.is_empty()
+    }
+}
+
+/// Run the first build attempt `Builder::with_config(cfg).build(keys)` would make
+/// and record its peeling instead of assigning `g`.
+///
+/// Keys are not deduplicated: a duplicate pair shows up as two identical unpeeled
+/// edges. The graph is traced even for sets below 32 keys, which real builds store
+/// without one. Vary `cfg.salt` to look at other attempts.
+pub fn try_build_debug(keys: &[&[u8]], cfg: &BuildConfig) -> Result<BdzTrace, MphError> {
+    assert!(!keys.is_empty(), "empty key set is not supported");
+    cfg.validate()?;
+    let n = keys.len();
+    let m = build_vertex_count(n, cfg)?;
+    let salt = cfg.first_salt();
+    let (v0, v1, v2) = derive_vertices_with(n, &|i| keys[i], salt, m as u64);
+
+    // Success or not, the peeling state is left in the scratch.
+    let mut scratch = GraphScratch::default();
+    let _ = build_from_edges(
+        [&v0, &v1, &v2],
+        n,
+        m,
+        salt,
+        cfg.require_minimal,
+        &mut scratch,
+    );
+
+    let edges: Vec<[u32; 3]> = (0..n).map(|i| [v0[i], v1[i], v2[i]]).collect();
+    let peel_order: Vec<PeelStep> = scratch
+        .peel_order
+        .iter()
+        .map(|p| PeelStep {
+            edge: p.edge,
+            vertex: edges[p.edge as usize][p.pivot as usize],
+        })
+        .collect();
+    // Peeling leaves each pivot at degree 1; its one edge is gone, so count it out.
+    let mut final_degrees = scratch.deg;
+    for step in &peel_order {
+        final_degrees[step.vertex as usize] -= 1;
+    }
+    let unpeeled = (0..n as u32)
+        .filter(|&e| !scratch.removed[e as usize])
+        .collect();
+    Ok(BdzTrace {
+        salt,
+        m,
+        edges,
+        peel_order,
+        final_degrees,
+        unpeeled,
+    })
+}
@@ -1,21 +1,476 @@
+//! Reusable building blocks. Only [`PackedArray`] and [`FrontCoded`] are public, e.g.
+//! for compact `values[mph.index(key)]` tables and retained key lists.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug)]
-pub struct BitSet {
+pub(crate) struct BitSet {
     bits: Vec<u64>,
     n: usize,
 }
 impl BitSet {
     pub fn new(n: usize) -> Self {
-        let words = (n + 63) / 64;
+        let words = n.div_ceil(64);
         Self { bits: vec![0; words], n }
     }
     #[inline]
     pub fn test(&self, idx: usize) -> bool {
+        debug_assert!(idx < self.n);
         let (w, b) = (idx / 64, idx % 64);
         (self.bits[w] >> b) & 1 == 1
     }
     #[inline]
     pub fn set(&mut self, idx: usize) {
+        debug_assert!(idx < self.n);
         let (w, b) = (idx / 64, idx % 64);
         self.bits[w] |= 1u64 << b;
     }
+    /// Unset every bit, keeping the allocation.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+    /// Whether any of `positions` is set. Same answer as testing each one, but the
+    /// word loads are OR-ed together and checked once instead of branching per bit;
+    /// with AVX2 ("simd" feature, x86_64) four words are gathered at a time. `avx2`
+    /// comes from [`Avx2::detect`], done once by the caller outside its hot loop.
+    #[inline]
+    pub fn any_set(&self, positions: &[usize], avx2: Avx2) -> bool {
+        debug_assert!(positions.iter().all(|&p| p < self.n));
+        if avx2.0 && positions.len() >= 4 {
+            // Safety: AVX2 is available; every position is < n <= 64 * bits.len().
+            #[cfg(all(feature = "simd", target_arch = "x86_64", not(feature = "checked")))]
+            return unsafe { any_set_avx2(&self.bits, positions) };
+        }
+        any_set_scalar(&self.bits, positions)
+    }
+}
+
+/// Proof of the runtime AVX2 check for [`BitSet::any_set`]; always false unless the
+/// AVX2 path is compiled in ("simd" without "checked", on x86_64).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Avx2(bool);
+
+impl Avx2 {
+    pub fn detect() -> Self {
+        #[cfg(all(feature = "simd", target_arch = "x86_64", not(feature = "checked")))]
+        {
+            Self(std::arch::is_x86_feature_detected!("avx2"))
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64", not(feature = "checked"))))]
+        {
+            Self(false)
+        }
+    }
+}
+
+#[inline]
+fn any_set_scalar(bits: &[u64], positions: &[usize]) -> bool {
+    let mut acc = 0u64;
+    for &p in positions {
+        // Safety: p < n <= 64 * bits.len() (checked under "checked")
+        acc |= unsafe { *get_at(bits, p / 64) } >> (p % 64);
+    }
+    acc & 1 == 1
+}
+
+/// # Safety
+/// AVX2 must be available and every `p` in `positions` must satisfy `p / 64 < bits.len()`.
+#[cfg(all(feature = "simd", target_arch = "x86_64", not(feature = "checked")))]
+#[target_feature(enable = "avx2")]
+unsafe fn any_set_avx2(bits: &[u64], positions: &[usize]) -> bool {
+    use std::arch::x86_64::*;
+
+    let chunks = positions.chunks_exact(4);
+    let tail = chunks.remainder();
+    let low6 = _mm256_set1_epi64x(63);
+    let mut acc = _mm256_setzero_si256();
+    for c in chunks {
+        // Safety: `c` holds four `usize` (= u64 on x86_64); gathered words are in
+        // bounds per the caller's contract.
+        unsafe {
+            let p = _mm256_loadu_si256(c.as_ptr() as *const __m256i);
+            let w = _mm256_srli_epi64::<6>(p);
+            let words = _mm256_i64gather_epi64::<8>(bits.as_ptr() as *const i64, w);
+            acc = _mm256_or_si256(acc, _mm256_srlv_epi64(words, _mm256_and_si256(p, low6)));
+        }
+    }
+    _mm256_testz_si256(acc, _mm256_set1_epi64x(1)) == 0 || any_set_scalar(bits, tail)
+}
+
+/// Fixed-width bit-packed array of `u64` values (`width` in `0..=64` bits per element).
+///
+/// Deserialization checks that the word buffer matches `len` and `width`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawPackedArray"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedArray {
+    words: Vec<u64>,
+    len: usize,
+    width: u8,
+}
+
+/// Unvalidated serialized form of [`PackedArray`].
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawPackedArray {
+    words: Vec<u64>,
+    len: usize,
+    width: u8,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawPackedArray> for PackedArray {
+    type Error = String;
+
+    fn try_from(raw: RawPackedArray) -> Result<Self, Self::Error> {
+        let bits = (raw.width <= 64)
+            .then(|| raw.len.checked_mul(raw.width as usize))
+            .flatten()
+            .ok_or("packed array width or length out of range")?;
+        if raw.words.len() != bits.div_ceil(64) {
+            return Err("packed array word count does not match len * width".into());
+        }
+        Ok(Self {
+            words: raw.words,
+            len: raw.len,
+            width: raw.width,
+        })
+    }
+}
+
+impl PackedArray {
+    /// Zero-filled array of `len` elements, `width` bits each.
+    pub fn new(len: usize, width: u8) -> Self {
+        assert!(width <= 64, "width must be <= 64 bits");
+        Self {
+            words: vec![0u64; (len * width as usize).div_ceil(64)],
+            len,
+            width,
+        }
+    }
+
+    /// Pack `values` using the minimum width that fits the largest one.
+    pub fn from_slice(values: &[u64]) -> Self {
+        let max = values.iter().copied().max().unwrap_or(0);
+        let mut out = Self::new(values.len(), bit_width(max));
+        for (i, &v) in values.iter().enumerate() {
+            out.set(i, v);
+        }
+        out
+    }
+
+    /// Store `value` at `idx`. Panics if `value` needs more than `width` bits.
+    #[inline]
+    pub fn set(&mut self, idx: usize, value: u64) {
+        assert!(idx < self.len, "index out of bounds");
+        assert!(
+            bit_width(value) <= self.width,
+            "value does not fit in {} bits",
+            self.width
+        );
+        if self.width == 0 {
+            return;
+        }
+        let mask = u64::MAX >> (64 - self.width as u32);
+        let (w, s) = slot(idx, self.width);
+        self.words[w] = (self.words[w] & !(mask << s)) | (value << s);
+        if s + self.width as usize > 64 {
+            let hi = 64 - s;
+            self.words[w + 1] = (self.words[w + 1] & !(mask >> hi)) | (value >> hi);
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.words.shrink_to_fit();
+    }
+
+    /// Bits used per element.
+    #[inline]
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Heap bytes held by the packed words.
+    pub fn size_bytes(&self) -> usize {
+        self.words.len() * std::mem::size_of::<u64>()
+    }
+
+    #[inline]
+    pub fn get(&self, idx: usize) -> u64 {
+        assert!(idx < self.len, "index out of bounds");
+        // SAFETY: idx < len checked above
+        unsafe { self.get_unchecked(idx) }
+    }
+
+    /// # Safety
+    /// `idx` must be `< self.len()`.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, idx: usize) -> u64 {
+        if self.width == 0 {
+            return 0;
+        }
+        let (w, s) = slot(idx, self.width);
+        let mask = u64::MAX >> (64 - self.width as u32);
+        let mut v = unsafe { *get_at(&self.words, w) } >> s;
+        if s + self.width as usize > 64 {
+            v |= unsafe { *get_at(&self.words, w + 1) } << (64 - s);
+        }
+        v & mask
+    }
+}
+
+/// Keys per [`FrontCoded`] bucket.
+const FRONT_CODED_BUCKET: usize = 16;
+
+/// Front-coded (prefix-compressed) list of byte strings.
+///
+/// Entries are grouped in buckets of 16: the first of each is stored whole, every
+/// other one as the length of the prefix it shares with its predecessor plus the
+/// remaining bytes (lengths as LEB128 varints). [`get`](Self::get) decodes from the
+/// start of the entry's bucket, so it walks at most 16 entries and allocates the
+/// result. Any order is accepted, but only sorted (or otherwise prefix-clustered)
+/// input compresses.
+///
+/// Deserialization decodes every entry once to check the blob.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawFrontCoded"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontCoded {
+    blob: Vec<u8>,
+    starts: Vec<usize>, // blob offset of each bucket
+    len: usize,
+}
+
+/// Unvalidated serialized form of [`FrontCoded`].
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawFrontCoded {
+    blob: Vec<u8>,
+    starts: Vec<usize>,
+    len: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawFrontCoded> for FrontCoded {
+    type Error = String;
+
+    fn try_from(raw: RawFrontCoded) -> Result<Self, Self::Error> {
+        let fc = Self {
+            blob: raw.blob,
+            starts: raw.starts,
+            len: raw.len,
+        };
+        if fc.starts.len() != fc.len.div_ceil(FRONT_CODED_BUCKET) {
+            return Err("front-coded bucket count does not match len".into());
+        }
+        // Decode the way `get` does, restarting at every bucket offset.
+        let (mut pos, mut key) = (0, Vec::new());
+        for i in 0..fc.len {
+            let first = i % FRONT_CODED_BUCKET == 0;
+            if first {
+                pos = fc.starts[i / FRONT_CODED_BUCKET];
+            }
+            pos = fc.decode_at(pos, first, &mut key).ok_or("front-coded blob is corrupt")?;
+        }
+        Ok(fc)
+    }
+}
+
+impl FrontCoded {
+    /// Encode `keys` in the given order.
+    pub fn new<K: AsRef<[u8]>>(keys: impl IntoIterator<Item = K>) -> Self {
+        let (mut blob, mut starts, mut len) = (Vec::new(), Vec::new(), 0);
+        let mut prev: Vec<u8> = Vec::new();
+        for k in keys {
+            let k = k.as_ref();
+            let shared = if len % FRONT_CODED_BUCKET == 0 {
+                starts.push(blob.len());
+                0
+            } else {
+                let shared = prev.iter().zip(k).take_while(|(a, b)| a == b).count();
+                put_varint(&mut blob, shared);
+                shared
+            };
+            put_varint(&mut blob, k.len() - shared);
+            blob.extend_from_slice(&k[shared..]);
+            prev.clear();
+            prev.extend_from_slice(k);
+            len += 1;
+        }
+        Self { blob, starts, len }
+    }
+
+    /// Entry `idx`. Panics if `idx >= len()`.
+    pub fn get(&self, idx: usize) -> Vec<u8> {
+        assert!(idx < self.len, "index out of bounds");
+        let mut key = Vec::new();
+        let bucket = idx - idx % FRONT_CODED_BUCKET;
+        let mut pos = self.starts[idx / FRONT_CODED_BUCKET];
+        for i in bucket..=idx {
+            pos = self
+                .decode_at(pos, i == bucket, &mut key)
+                .expect("validated on construction");
+        }
+        key
+    }
+
+    /// All entries in order, decoded one after the other.
+    pub fn iter(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let (mut pos, mut key) = (0, Vec::new());
+        (0..self.len).map(move |i| {
+            let first = i % FRONT_CODED_BUCKET == 0;
+            if first {
+                pos = self.starts[i / FRONT_CODED_BUCKET];
+            }
+            pos = self
+                .decode_at(pos, first, &mut key)
+                .expect("validated on construction");
+            key.clone()
+        })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Heap bytes held: the encoded blob plus one offset per bucket.
+    pub fn size_bytes(&self) -> usize {
+        self.blob.len() + self.starts.len() * std::mem::size_of::<usize>()
+    }
+
+    /// Decode the entry at blob offset `pos` over `key` (its predecessor, unless
+    /// `first`), returning the offset of the next entry.
+    fn decode_at(&self, mut pos: usize, first: bool, key: &mut Vec<u8>) -> Option<usize> {
+        let shared = if first { 0 } else { get_varint(&self.blob, &mut pos)? };
+        let rest = get_varint(&self.blob, &mut pos)?;
+        if shared > key.len() {
+            return None;
+        }
+        let end = pos.checked_add(rest).filter(|&e| e <= self.blob.len())?;
+        key.truncate(shared);
+        key.extend_from_slice(&self.blob[pos..end]);
+        Some(end)
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut v: usize) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn get_varint(blob: &[u8], pos: &mut usize) -> Option<usize> {
+    let mut v = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let byte = *blob.get(*pos)?;
+        *pos += 1;
+        v |= ((byte & 0x7F) as usize).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(v);
+        }
+    }
+    None
+}
+
+/// `slice.get_unchecked(i)`, bounds-checked instead under the "checked" feature so
+/// Miri and fuzzers see a violated invariant as a panic rather than UB.
+///
+/// # Safety
+/// `i < s.len()` (always upheld when "checked" is enabled, via panic).
+#[inline(always)]
+pub(crate) unsafe fn get_at<T>(s: &[T], i: usize) -> &T {
+    #[cfg(feature = "checked")]
+    {
+        &s[i]
+    }
+    #[cfg(not(feature = "checked"))]
+    {
+        unsafe { s.get_unchecked(i) }
+    }
+}
+
+/// Mutable counterpart of [`get_at`].
+///
+/// # Safety
+/// `i < s.len()` (always upheld when "checked" is enabled, via panic).
+#[inline(always)]
+pub(crate) unsafe fn get_at_mut<T>(s: &mut [T], i: usize) -> &mut T {
+    #[cfg(feature = "checked")]
+    {
+        &mut s[i]
+    }
+    #[cfg(not(feature = "checked"))]
+    {
+        unsafe { s.get_unchecked_mut(i) }
+    }
+}
+
+/// Minimal number of bits needed to represent `v` (0 for 0).
+#[inline]
+pub(crate) fn bit_width(v: u64) -> u8 {
+    (64 - v.leading_zeros()) as u8
+}
+
+#[inline]
+fn slot(idx: usize, width: u8) -> (usize, usize) {
+    let bit = idx * width as usize;
+    (bit / 64, bit % 64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// The scalar loop, the AVX2 gather (when this CPU has it) and testing each bit on
+    /// its own must give the same answer, including for the tail past the last group
+    /// of four positions.
+    #[test]
+    fn any_set_paths_agree() {
+        let avx2 = Avx2::detect();
+        let mut rng = StdRng::seed_from_u64(0x5EED);
+        for round in 0..2000 {
+            let n = 64 * rng.gen_range(1..6);
+            let mut set = BitSet::new(n);
+            match round % 3 {
+                0 => (0..n).filter(|_| rng.r#gen()).for_each(|p| set.set(p)),
+                // A single bit, so most probes miss and every lane matters.
+                1 => set.set(rng.gen_range(0..n)),
+                _ => {}
+            }
+            for len in 0..=17 {
+                let positions: Vec<usize> = (0..len).map(|_| rng.gen_range(0..n)).collect();
+                let per_bit = positions.iter().any(|&p| set.test(p));
+                assert_eq!(any_set_scalar(&set.bits, &positions), per_bit);
+                assert_eq!(set.any_set(&positions, avx2), per_bit, "{positions:?}");
+                #[cfg(all(feature = "simd", target_arch = "x86_64", not(feature = "checked")))]
+                if avx2.0 {
+                    // Safety: AVX2 is available; every position is < n.
+                    let gathered = unsafe { any_set_avx2(&set.bits, &positions) };
+                    assert_eq!(gathered, per_bit, "{positions:?}");
+                }
+            }
+        }
+    }
 }
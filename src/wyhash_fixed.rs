@@ -0,0 +1,90 @@
+//! Length-specialized port of `wyhash::wyhash` (v1) for fixed-size keys.
+//!
+//! Must stay bit-for-bit identical to the crate's generic hash: keys hashed here
+//! and through `wyhash::wyhash` land on the same vertices.
+
+const P0: u64 = 0xa076_1d64_78bd_642f;
+const P1: u64 = 0xe703_7ed1_a0b4_28db;
+const P2: u64 = 0x8ebc_6af0_9c88_c6e3;
+const P3: u64 = 0x5899_65cc_7537_4cc3;
+const P4: u64 = 0x1d8e_4e27_c47d_124f;
+const P5: u64 = 0xeb44_acca_b455_d165;
+
+/// `wyhash::wyhash(key, seed)` with `N` known at compile time, so the chunk loop and
+/// the tail dispatch fold away after inlining.
+#[inline(always)]
+pub(crate) fn wyhash_fixed<const N: usize>(key: &[u8; N], seed: u64) -> u64 {
+    let bytes = &key[..];
+    let mut seed = seed;
+    for chunk in bytes.chunks_exact(32) {
+        seed = wymum(
+            seed ^ P0,
+            wymum(read64(chunk) ^ P1, read64(&chunk[8..]) ^ P2)
+                ^ wymum(read64(&chunk[16..]) ^ P3, read64(&chunk[24..]) ^ P4),
+        );
+    }
+    seed ^= P0;
+
+    if N & 31 != 0 {
+        let start = N & !31;
+        seed = match ((N - 1) & 31) / 8 {
+            0 => wymum(seed, read_rest(&bytes[start..]) ^ P1),
+            1 => wymum(
+                read64_swapped(&bytes[start..]) ^ seed,
+                read_rest(&bytes[start + 8..]) ^ P2,
+            ),
+            2 => {
+                wymum(
+                    read64_swapped(&bytes[start..]) ^ seed,
+                    read64_swapped(&bytes[start + 8..]) ^ P2,
+                ) ^ wymum(seed, read_rest(&bytes[start + 16..]) ^ P3)
+            }
+            _ => {
+                wymum(
+                    read64_swapped(&bytes[start..]) ^ seed,
+                    read64_swapped(&bytes[start + 8..]) ^ P2,
+                ) ^ wymum(
+                    read64_swapped(&bytes[start + 16..]) ^ seed,
+                    read_rest(&bytes[start + 24..]) ^ P4,
+                )
+            }
+        };
+    }
+    wymum(seed, N as u64 ^ P5)
+}
+
+#[inline(always)]
+fn wymum(a: u64, b: u64) -> u64 {
+    let r = u128::from(a) * u128::from(b);
+    ((r >> 64) ^ r) as u64
+}
+
+#[inline(always)]
+fn read64(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[..8].try_into().unwrap())
+}
+
+#[inline(always)]
+fn read32(data: &[u8]) -> u64 {
+    u32::from_le_bytes(data[..4].try_into().unwrap()) as u64
+}
+
+#[inline(always)]
+fn read64_swapped(data: &[u8]) -> u64 {
+    (read32(data) << 32) | read32(&data[4..])
+}
+
+#[inline(always)]
+fn read_rest(data: &[u8]) -> u64 {
+    let b = |i: usize| u64::from(data[i]);
+    match data.len() {
+        1 => b(0),
+        2 => (b(1) << 8) | b(0),
+        3 => (b(1) << 16) | (b(0) << 8) | b(2),
+        4 => read32(data),
+        5 => (read32(data) << 8) | b(4),
+        6 => (read32(data) << 16) | (b(5) << 8) | b(4),
+        7 => (read32(data) << 24) | (b(5) << 16) | (b(4) << 8) | b(6),
+        _ => read64_swapped(data),
+    }
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::{BuildConfig, MphError, Mphf};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+fn cfg(minimal: bool) -> BuildConfig {
+    BuildConfig {
+        fingerprint: true,
+        require_minimal: minimal,
+        ..Default::default()
+    }
+}
+
+fn shuffled(range: u64, rng: &mut StdRng) -> Vec<u32> {
+    let mut perm: Vec<u32> = (0..range as u32).collect();
+    perm.shuffle(rng);
+    perm
+}
+
+#[test]
+fn indices_follow_the_permutation_and_compose() {
+    let mut rng = StdRng::seed_from_u64(369);
+    for (n, minimal) in [(20, true), (1000, true), (1000, false)] {
+        let keys = keys("perm", n);
+        let mut mph = build(&keys, cfg(minimal));
+        let old: Vec<u64> = keys.iter().map(|k| mph.index_str(k)).collect();
+
+        let first = shuffled(mph.range(), &mut rng);
+        mph.apply_permutation(&first).unwrap();
+        for (k, &o) in keys.iter().zip(&old) {
+            assert_eq!(mph.index_str(k), first[o as usize] as u64);
+        }
+
+        let second = shuffled(mph.range(), &mut rng);
+        mph.apply_permutation(&second).unwrap();
+        for (k, &o) in keys.iter().zip(&old) {
+            let composed = second[first[o as usize] as usize] as u64;
+            assert_eq!(mph.index_str(k), composed, "n={n} {k}");
+            assert!(mph.contains_index(composed));
+        }
+    }
+}
+
+#[test]
+fn fingerprints_move_with_their_keys() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let keys = keys("perm", 3000);
+    let mut mph = build(&keys, cfg(true));
+    let outsiders = |mph: &Mphf| {
+        (0..20_000)
+            .filter(|i| mph.index_if_member_str(&format!("outsider-{i}")).is_some())
+            .count()
+    };
+    let before = outsiders(&mph);
+    for _ in 0..3 {
+        let perm = shuffled(mph.range(), &mut rng);
+        mph.apply_permutation(&perm).unwrap();
+        for k in &keys {
+            assert_eq!(mph.index_if_member_str(k), Some(mph.index_str(k)), "{k}");
+        }
+    }
+    // Non-members are still rejected at the same rate, as each fingerprint moved as a
+    // whole rather than being reset or scrambled.
+    assert_eq!(outsiders(&mph), before);
+}
+
+#[test]
+fn invalid_permutations_are_rejected_unchanged() {
+    let keys = keys("perm", 500);
+    let mut mph = build(&keys, cfg(true));
+    let identity: Vec<u32> = (0..500).collect();
+    let mut repeated = identity.clone();
+    repeated[10] = 11;
+    let mut out_of_range = identity.clone();
+    out_of_range[499] = 500;
+    let bad = [
+        identity[..499].to_vec(),
+        [identity.clone(), vec![500]].concat(),
+        repeated,
+        out_of_range,
+        vec![u32::MAX; 500],
+    ];
+    let before: Vec<u64> = keys.iter().map(|k| mph.index_str(k)).collect();
+    for perm in &bad {
+        let res = mph.apply_permutation(perm);
+        assert!(matches!(res, Err(MphError::InvalidConfig(_))), "{perm:?}");
+    }
+    let after: Vec<u64> = keys.iter().map(|k| mph.index_str(k)).collect();
+    assert_eq!(before, after);
+}
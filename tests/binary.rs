@@ -0,0 +1,122 @@
+#![cfg(feature = "binary")]
+
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::{BuildConfig, MphError, Mphf};
+
+// Field offsets of the MPHB header.
+const FLAGS_AT: usize = 5;
+const N_AT: usize = 6;
+const M_AT: usize = 14;
+
+/// Minimal, with fingerprints and with a remap table.
+fn full_blob() -> Vec<u8> {
+    let cfg = BuildConfig {
+        fingerprint: true,
+        ..Default::default()
+    };
+    let mut mph = build(&keys("mphb", 500), cfg);
+    let perm: Vec<u32> = (0..mph.range() as u32).rev().collect();
+    mph.apply_permutation(&perm).unwrap();
+    mph.to_binary()
+}
+
+fn assert_binary_err(bytes: &[u8], what: &str) {
+    assert!(
+        matches!(Mphf::from_binary(bytes), Err(MphError::Binary(_))),
+        "{what}"
+    );
+}
+
+#[test]
+fn round_trip_keeps_every_field() {
+    for (n, fingerprint, require_minimal, permute) in [
+        (10, false, true, false),
+        (500, false, true, false),
+        (500, true, true, false),
+        (500, true, true, true),
+        (500, false, false, false),
+        (500, true, false, true),
+    ] {
+        let cfg = BuildConfig {
+            fingerprint,
+            require_minimal,
+            ..Default::default()
+        };
+        let keys = keys("mphb", n);
+        let mut mph = build(&keys, cfg);
+        if permute {
+            let perm: Vec<u32> = (0..mph.range() as u32).rev().collect();
+            mph.apply_permutation(&perm).unwrap();
+        }
+        let blob = mph.to_binary();
+        let back = Mphf::from_binary(&blob).unwrap();
+        let what = format!("n={n} fp={fingerprint} minimal={require_minimal} perm={permute}");
+        assert_eq!(
+            (back.n, back.m, back.salt),
+            (mph.n, mph.m, mph.salt),
+            "{what}"
+        );
+        assert_eq!(back.g, mph.g, "{what}");
+        assert_eq!(back.fingerprints, mph.fingerprints, "{what}");
+        assert_eq!(back.remap, mph.remap, "{what}");
+        assert_eq!(back.is_minimal(), mph.is_minimal(), "{what}");
+        for k in &keys {
+            let k = k.as_bytes();
+            assert_eq!(back.index(k), mph.index(k), "{what}");
+            assert_eq!(back.index_if_member(k), mph.index_if_member(k), "{what}");
+        }
+        assert_eq!(back.to_binary(), blob, "{what}");
+    }
+}
+
+#[test]
+fn every_truncation_and_trailing_bytes_are_rejected() {
+    let blob = full_blob();
+    for len in 0..blob.len() {
+        assert_binary_err(&blob[..len], &format!("prefix {len}"));
+    }
+    let mut longer = blob.clone();
+    longer.push(0);
+    assert_binary_err(&longer, "trailing byte");
+}
+
+#[test]
+fn bad_magic_is_rejected() {
+    let mut blob = full_blob();
+    blob[..4].copy_from_slice(b"MPHF");
+    assert_binary_err(&blob, "magic");
+}
+
+#[test]
+fn unknown_flag_bits_are_rejected() {
+    for bit in 3..8 {
+        let mut blob = full_blob();
+        blob[FLAGS_AT] |= 1 << bit;
+        assert_binary_err(&blob, &format!("flag bit {bit}"));
+    }
+}
+
+#[test]
+fn n_outside_1_to_m_is_rejected() {
+    let blob = full_blob();
+    let m = u32::from_le_bytes(blob[M_AT..M_AT + 4].try_into().unwrap()) as u64;
+    for n in [0, m + 1, u64::MAX] {
+        let mut bad = blob.clone();
+        bad[N_AT..N_AT + 8].copy_from_slice(&n.to_le_bytes());
+        assert_binary_err(&bad, &format!("n={n}"));
+    }
+}
+
+#[test]
+fn remap_entries_past_the_range_are_rejected() {
+    let blob = full_blob();
+    // The remap table (500 u32s, range = n) ends the blob.
+    let last = blob.len() - 4;
+    for entry in [500u32, u32::MAX] {
+        let mut bad = blob.clone();
+        bad[last..].copy_from_slice(&entry.to_le_bytes());
+        assert_binary_err(&bad, &format!("remap entry {entry}"));
+    }
+}
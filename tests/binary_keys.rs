@@ -0,0 +1,76 @@
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{Builder, MphError};
+
+fn assert_bijection(keys: &[&[u8]]) {
+    let mph = Builder::new().build(keys.iter().copied()).unwrap();
+    assert_minimal_perfect(&mph, keys);
+}
+
+#[test]
+fn empty_key_is_valid() {
+    assert_bijection(&[b"", b"a", b"b", b"c"]);
+}
+
+#[test]
+fn trailing_nul_makes_keys_distinct() {
+    assert_bijection(&[b"a", b"a\0", b"a\0\0", b"\0", b"\0a", b""]);
+}
+
+#[test]
+fn embedded_nul_binary_keys() {
+    let keys: Vec<Vec<u8>> = (0u32..1000)
+        .map(|i| {
+            let b = i.to_le_bytes();
+            vec![0, b[0], 0, b[1], 0]
+        })
+        .collect();
+    let refs: Vec<&[u8]> = keys.iter().map(|k| k.as_slice()).collect();
+    assert_bijection(&refs);
+}
+
+#[test]
+fn duplicate_detected_on_exact_bytes() {
+    let dup = Builder::new().build([&b"x\0"[..], b"y", b"x\0"]);
+    assert!(matches!(dup, Err(MphError::DuplicateKey)));
+
+    let empty_dup = Builder::new().build([&b""[..], b"y", b""]);
+    assert!(matches!(empty_dup, Err(MphError::DuplicateKey)));
+}
+
+#[test]
+fn build_checked_accepts_binary_keys() {
+    let keys: [&[u8]; 5] = [b"", b"\0", b"\xff\xfe", b"a\0b", b"a"];
+    let mph = Builder::new().build_checked(keys).unwrap();
+    assert_minimal_perfect(&mph, &keys);
+    assert!(matches!(
+        Builder::new().build_checked([b"x" as &[u8], b"x"]),
+        Err(MphError::DuplicateKey)
+    ));
+}
+
+#[test]
+fn string_membership_matches_bytes() {
+    let keys: Vec<String> = (0..500).map(|i| format!("ключ-{i}")).collect();
+    let cfg = minimal_perfect_hash::BuildConfig {
+        fingerprint: true,
+        ..Default::default()
+    };
+    let mph = Builder::new()
+        .with_config(cfg)
+        .build(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    for k in &keys {
+        assert_eq!(mph.index_if_member_str(k), Some(mph.index_str(k)));
+    }
+    let rejected = (0..2000)
+        .map(|i| format!("other-{i}"))
+        .filter(|s| mph.index_if_member_str(s).is_none())
+        .count();
+    assert!(rejected > 1900, "{rejected}");
+    for s in ["", "other-1", "ключ"] {
+        assert_eq!(
+            mph.index_if_member_str(s),
+            mph.index_if_member(s.as_bytes())
+        );
+    }
+}
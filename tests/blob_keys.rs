@@ -0,0 +1,65 @@
+mod common;
+
+use common::keys;
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, Builder, MphError};
+
+fn columnar(keys: &[&[u8]]) -> (Vec<u8>, Vec<u32>) {
+    let mut blob = Vec::new();
+    let mut offsets = vec![0u32];
+    for k in keys {
+        blob.extend_from_slice(k);
+        offsets.push(blob.len() as u32);
+    }
+    (blob, offsets)
+}
+
+#[test]
+fn blob_keys_match_slice_keys() {
+    let owned = keys("blob", 5000);
+    let mut keys: Vec<&[u8]> = owned.iter().map(|k| k.as_bytes()).collect();
+    keys.push(b""); // zero-length key: two equal offsets
+    let (blob, offsets) = columnar(&keys);
+
+    let mph = Builder::new().build_from_blob(&blob, &offsets).unwrap();
+    assert_minimal_perfect(&mph, &keys);
+}
+
+#[test]
+fn invalid_offsets_are_rejected() {
+    let (blob, offsets) = columnar(&[b"a", b"bb", b"ccc"]);
+    let build = |offsets: &[u32]| Builder::new().build_from_blob(&blob, offsets);
+    assert!(matches!(
+        build(&[0, 3, 1, 6]),
+        Err(MphError::InvalidOffsets)
+    ));
+    assert!(matches!(
+        build(&[0, 1, 3, 7]),
+        Err(MphError::InvalidOffsets)
+    ));
+    assert!(build(&offsets).is_ok());
+    // A suffix of the blob is fine as well.
+    assert!(build(&[1, 3, 6]).is_ok());
+}
+
+#[test]
+fn offsets_without_a_key_are_an_empty_set() {
+    let blob = b"abc";
+    for offsets in [&[][..], &[0], &[3]] {
+        let r = Builder::new().build_from_blob(blob, offsets);
+        assert!(matches!(r, Err(MphError::EmptyKeySet)), "{offsets:?}");
+    }
+}
+
+#[test]
+fn duplicates_follow_check_unique_indexed() {
+    let (blob, offsets) = columnar(&[b"x", b"y", b"x"]);
+    let cfg = BuildConfig {
+        check_unique_indexed: true,
+        ..Default::default()
+    };
+    let r = Builder::new()
+        .with_config(cfg)
+        .build_from_blob(&blob, &offsets);
+    assert!(matches!(r, Err(MphError::DuplicateKey)));
+}
@@ -0,0 +1,71 @@
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, Builder, MphError};
+
+/// Keys `"indexed-{i}"` stored back to back, with their end offsets.
+fn arena(n: usize) -> (String, Vec<usize>) {
+    let mut blob = String::new();
+    let mut ends = Vec::with_capacity(n);
+    for i in 0..n {
+        blob.push_str(&format!("indexed-{i}"));
+        ends.push(blob.len());
+    }
+    (blob, ends)
+}
+
+fn key<'a>(blob: &'a str, ends: &[usize], i: usize) -> &'a [u8] {
+    let start = if i == 0 { 0 } else { ends[i - 1] };
+    &blob.as_bytes()[start..ends[i]]
+}
+
+#[test]
+fn callback_build_matches_build_with_and_without_the_check() {
+    for n in [1, 31, 500, 20_000] {
+        let (blob, ends) = arena(n);
+        let direct = Builder::new()
+            .build((0..n).map(|i| key(&blob, &ends, i)))
+            .unwrap();
+        for check in [false, true] {
+            let cfg = BuildConfig {
+                check_unique_indexed: check,
+                ..Default::default()
+            };
+            let mph = Builder::new()
+                .with_config(cfg)
+                .build_indexed(n, |i| key(&blob, &ends, i))
+                .unwrap();
+            assert_eq!(mph.n, n as u64);
+            assert_eq!(
+                (mph.m, mph.salt, &mph.g),
+                (direct.m, direct.salt, &direct.g)
+            );
+            let keys: Vec<&[u8]> = (0..n).map(|i| key(&blob, &ends, i)).collect();
+            assert_minimal_perfect(&mph, &keys);
+        }
+    }
+}
+
+#[test]
+fn duplicates_are_caught_only_with_the_check() {
+    let n = 1000;
+    let (blob, ends) = arena(n);
+    // Index n reads key 123 again.
+    let get = |i: usize| key(&blob, &ends, if i == n { 123 } else { i });
+
+    let checked = BuildConfig {
+        check_unique_indexed: true,
+        ..Default::default()
+    };
+    let res = Builder::new()
+        .with_config(checked)
+        .build_indexed(n + 1, get);
+    assert!(matches!(res, Err(MphError::DuplicateKey)));
+
+    let unchecked = BuildConfig {
+        rehash_limit: 2,
+        ..Default::default()
+    };
+    let res = Builder::new()
+        .with_config(unchecked)
+        .build_indexed(n + 1, get);
+    assert!(matches!(res, Err(MphError::Unresolvable { .. })));
+}
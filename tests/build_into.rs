@@ -0,0 +1,73 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::{BuildConfig, Builder, MphError, Mphf};
+use std::collections::HashSet;
+
+/// `mph` is a bijection onto its range over exactly `keys`, and the same MPH a fresh
+/// builder makes under `cfg`.
+fn assert_fresh(mph: &Mphf, keys: &[String], cfg: &BuildConfig) {
+    assert_eq!(mph.n, keys.len() as u64);
+    let idx: HashSet<u64> = keys.iter().map(|k| mph.index_str(k)).collect();
+    assert_eq!(idx.len(), keys.len());
+    assert!(idx.iter().all(|&i| mph.contains_index(i)));
+    let fresh = build(keys, cfg.clone());
+    assert_eq!((mph.m, mph.salt, &mph.g), (fresh.m, fresh.salt, &fresh.g));
+    assert_eq!(mph.fingerprints, fresh.fingerprints);
+    assert_eq!(mph.is_minimal(), fresh.is_minimal());
+}
+
+#[test]
+fn successive_builds_share_nothing_but_buffers() {
+    let non_minimal = BuildConfig {
+        require_minimal: false,
+        fingerprint: true,
+        ..Default::default()
+    };
+    let low_memory = BuildConfig {
+        low_memory_peel: true,
+        salt: 7,
+        ..Default::default()
+    };
+    let runs = [
+        (keys("a", 5000), BuildConfig::default()),
+        (keys("b", 10), BuildConfig::default()),
+        (keys("c", 300), non_minimal),
+        (keys("d", 31), low_memory.clone()),
+        (keys("e", 2000), low_memory),
+        (keys("f", 40), BuildConfig::compact()),
+    ];
+    let mut builder = Builder::new();
+    let mut out = None;
+    for (keys, cfg) in &runs {
+        builder = builder.with_config(cfg.clone());
+        builder
+            .build_into(keys.iter().map(|k| k.as_bytes()), &mut out)
+            .unwrap();
+        assert_fresh(out.as_ref().unwrap(), keys, cfg);
+    }
+}
+
+#[test]
+fn a_failed_build_leaves_out_empty_and_the_next_one_clean() {
+    let mut builder = Builder::new();
+    let mut out = None;
+    let first = keys("x", 1000);
+    builder
+        .build_into(first.iter().map(|k| k.as_bytes()), &mut out)
+        .unwrap();
+
+    let mut dup = keys("y", 500);
+    dup.push("y-17".into());
+    dup.extend(keys("z", 100));
+    let err = builder.build_into(dup.iter().map(|k| k.as_bytes()), &mut out);
+    assert!(matches!(err, Err(MphError::DuplicateKey)));
+    assert!(out.is_none());
+
+    // The keys pushed before the duplicate are forgotten.
+    let next = keys("y", 400);
+    builder
+        .build_into(next.iter().map(|k| k.as_bytes()), &mut out)
+        .unwrap();
+    assert_fresh(out.as_ref().unwrap(), &next, &BuildConfig::default());
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use common::{build, byte_keys};
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, Builder, MphError};
+
+#[test]
+fn owned_build_matches_borrowed_build() {
+    // The last size takes the parallel dedup path when that feature is on.
+    for n in [1, 31, 32, 5000, (1 << 17) + 10] {
+        let keys = byte_keys("owned", n);
+        let cfg = BuildConfig {
+            fingerprint: true,
+            ..Default::default()
+        };
+        let borrowed = build(&keys, cfg.clone());
+        let owned = Builder::new()
+            .with_config(cfg)
+            .build_owned(keys.clone())
+            .unwrap();
+        assert_eq!(owned.n, n as u64);
+        assert_eq!(
+            (owned.m, owned.salt, &owned.g),
+            (borrowed.m, borrowed.salt, &borrowed.g)
+        );
+        assert_eq!(owned.fingerprints, borrowed.fingerprints);
+        assert_minimal_perfect(&owned, &keys);
+    }
+}
+
+#[test]
+fn duplicates_are_rejected() {
+    for n in [10, 1000, (1 << 17) + 10] {
+        let mut keys = byte_keys("owned", n);
+        keys.insert(n / 3, keys[n - 1].clone());
+        let res = Builder::new().build_owned(keys);
+        assert!(matches!(res, Err(MphError::DuplicateKey)), "n={n}");
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::keys;
+use minimal_perfect_hash::{Builder, MphError};
+use std::collections::HashSet;
+
+#[test]
+fn index_is_a_bijection_onto_all_keys() {
+    for n in [10, 2000, 50_000] {
+        let keys = keys("par", n);
+        for partitions in [0, 1, 3, 16] {
+            let mph = Builder::new().build_parallel(&keys, partitions).unwrap();
+            // Parts average at least 1024 keys.
+            assert!(
+                mph.parts().len() <= (n / 1024).max(1),
+                "n={n} p={partitions}"
+            );
+            assert_eq!(mph.n(), n as u64);
+            let idx: HashSet<u64> = keys.iter().map(|k| mph.index_str(k)).collect();
+            assert_eq!(idx.len(), n, "n={n} p={partitions}");
+            assert!(idx.iter().all(|&i| i < n as u64), "n={n} p={partitions}");
+        }
+    }
+}
+
+#[test]
+fn duplicates_fail_their_part() {
+    let mut keys = keys("par", 10_000);
+    keys.push(keys[1234].clone());
+    let res = Builder::new().build_parallel(&keys, 4);
+    assert!(matches!(res, Err(MphError::DuplicateKey)));
+}
+
+#[test]
+fn explicit_partitions_are_lowered_to_the_key_count() {
+    let keys = keys("par", 5000);
+    for (partitions, used) in [(1, 1), (3, 3), (4, 4), (5, 4), (64, 4)] {
+        let mph = Builder::new().build_parallel(&keys, partitions).unwrap();
+        assert_eq!(mph.parts().len(), used, "p={partitions}");
+    }
+}
+
+#[test]
+fn empty_input_is_an_empty_key_set() {
+    let res = Builder::new().build_parallel::<&[u8]>(&[], 4);
+    assert!(matches!(res, Err(MphError::EmptyKeySet)));
+}
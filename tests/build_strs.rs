@@ -0,0 +1,33 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, Builder, MphError};
+
+#[test]
+fn borrowed_strs_build_like_bytes() {
+    for n in [1, 31, 32, 4000] {
+        let words = keys("str", n);
+        let mph = Builder::new()
+            .build_strs(words.iter().map(String::as_str))
+            .unwrap();
+        let bytes = build(&words, BuildConfig::default());
+        assert_eq!(mph.n, n as u64);
+        assert_eq!((mph.m, mph.salt, &mph.g), (bytes.m, bytes.salt, &bytes.g));
+        assert_minimal_perfect(&mph, &words);
+    }
+}
+
+#[test]
+fn duplicate_strs_are_rejected() {
+    for n in [5, 4000] {
+        let mut words = keys("str", n);
+        words.push(words[0].clone());
+        let res = Builder::new().build_strs(words.iter().map(String::as_str));
+        assert!(matches!(res, Err(MphError::DuplicateKey)), "n={n}");
+    }
+    // Equal bytes from distinct allocations are still duplicates.
+    let (a, b) = (String::from("twin"), String::from("twin"));
+    let res = Builder::new().build_strs([a.as_str(), "other", b.as_str()]);
+    assert!(matches!(res, Err(MphError::DuplicateKey)));
+}
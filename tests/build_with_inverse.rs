@@ -0,0 +1,42 @@
+mod common;
+
+use common::keys;
+use minimal_perfect_hash::{BuildConfig, Builder};
+
+#[test]
+fn inverse_maps_each_index_back_to_its_key() {
+    for n in [1, 31, 32, 5000] {
+        let keys = keys("inverse", n);
+        let (mph, inverse) = Builder::new()
+            .build_with_inverse(keys.iter().map(|k| k.as_bytes()))
+            .unwrap();
+        assert_eq!(inverse.len(), n);
+        for (i, k) in inverse.iter().enumerate() {
+            assert_eq!(mph.index(k), i as u64, "n={n}");
+        }
+        let mut sorted: Vec<&[u8]> = inverse.iter().map(Vec::as_slice).collect();
+        sorted.sort_unstable();
+        let mut expected: Vec<&[u8]> = keys.iter().map(|k| k.as_bytes()).collect();
+        expected.sort_unstable();
+        assert_eq!(sorted, expected);
+    }
+}
+
+#[test]
+fn non_minimal_inverse_covers_the_whole_range() {
+    let keys = keys("inverse", 1000);
+    let cfg = BuildConfig {
+        require_minimal: false,
+        ..Default::default()
+    };
+    let (mph, inverse) = Builder::new()
+        .with_config(cfg)
+        .build_with_inverse(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    assert_eq!(inverse.len() as u64, mph.range());
+    for k in &keys {
+        assert_eq!(inverse[mph.index_str(k) as usize], k.as_bytes());
+    }
+    let unused = inverse.iter().filter(|k| k.is_empty()).count();
+    assert_eq!(unused as u64, mph.range() - 1000);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::keys;
+use minimal_perfect_hash::{BuildConfig, Builder, MIN_GAMMA_RANK3};
+
+#[test]
+fn calibrated_gamma_is_in_range_and_builds_the_sample() {
+    for (n, gamma) in [(2000, 1.27), (2000, 2.0), (20_000, 1.5)] {
+        let sample = keys("sample", n);
+        let sample: Vec<&[u8]> = sample.iter().map(|k| k.as_bytes()).collect();
+        let cfg = BuildConfig {
+            gamma,
+            ..Default::default()
+        };
+        let tuned = Builder::new().with_config(cfg).calibrate(&sample);
+        assert!(
+            (MIN_GAMMA_RANK3..=gamma).contains(&tuned.gamma),
+            "n={n}: {} outside [{MIN_GAMMA_RANK3}, {gamma}]",
+            tuned.gamma
+        );
+        let (mph, stats) = Builder::new()
+            .with_config(tuned.clone())
+            .build_with_stats(sample.iter().copied())
+            .unwrap();
+        assert!(!stats.padded);
+        assert_eq!(mph.m as u64, (tuned.gamma * n as f64).ceil() as u64);
+    }
+}
+
+#[test]
+fn small_builds_pad_the_calibrated_gamma() {
+    let sample = keys("small", 100);
+    let sample: Vec<&[u8]> = sample.iter().map(|k| k.as_bytes()).collect();
+    let tuned = Builder::new().calibrate(&sample);
+    assert!(tuned.gamma <= BuildConfig::default().gamma);
+    let (mph, stats) = Builder::new()
+        .with_config(tuned)
+        .build_with_stats(sample.iter().copied())
+        .unwrap();
+    assert!(stats.padded);
+    assert_eq!(mph.m, 150);
+}
+
+#[test]
+fn tiny_samples_leave_the_config_unchanged() {
+    let cfg = BuildConfig {
+        gamma: 1.8,
+        ..Default::default()
+    };
+    let tuned = Builder::new()
+        .with_config(cfg)
+        .calibrate(&[b"a".as_slice(), b"b"]);
+    assert_eq!(tuned.gamma, 1.8);
+}
@@ -0,0 +1,36 @@
+use minimal_perfect_hash::chd::{BuildConfig, Builder, MphError, Placement};
+
+#[test]
+fn scaled_attempt_caps_build_a_bijection() {
+    let keys: Vec<String> = (0..10_000).map(|i| format!("as-{i}")).collect();
+    let cfg = BuildConfig {
+        placement: Placement::Mixed,
+        attempt_scale: Some(20.0),
+        rehash_limit: 32,
+        ..Default::default()
+    };
+    let mph = Builder::new()
+        .with_config(cfg)
+        .build(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    let mut seen = vec![false; keys.len()];
+    for k in &keys {
+        let i = mph.index(k.as_bytes()) as usize;
+        assert!(!seen[i], "index {i} assigned twice");
+        seen[i] = true;
+    }
+}
+
+#[test]
+fn non_positive_or_non_finite_scale_is_rejected() {
+    for c in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let cfg = BuildConfig {
+            attempt_scale: Some(c),
+            ..Default::default()
+        };
+        let res = Builder::new()
+            .with_config(cfg)
+            .build([b"a".as_slice(), b"b"]);
+        assert!(matches!(res, Err(MphError::InvalidConfig(_))), "scale {c}");
+    }
+}
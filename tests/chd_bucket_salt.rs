@@ -0,0 +1,35 @@
+use minimal_perfect_hash::chd::{BuildConfig, Builder, KeyHash};
+use std::collections::HashSet;
+
+#[test]
+fn separate_bucket_salt_is_recorded_and_used() {
+    let keys: Vec<String> = (0..1000).map(|i| format!("bs-{i}")).collect();
+    let build = |bucket_salt| {
+        // Linear placement fails most single attempts at this size; leave room to rehash.
+        let cfg = BuildConfig {
+            bucket_salt,
+            rehash_limit: 32,
+            ..Default::default()
+        };
+        Builder::new()
+            .with_config(cfg)
+            .build(keys.iter().map(|k| k.as_bytes()))
+            .unwrap()
+    };
+
+    let coupled = build(None);
+    assert_eq!(coupled.bucket_salt, None);
+    let k = keys[0].as_bytes();
+    assert_eq!(
+        coupled.key_hash(k).h1,
+        KeyHash::from_key(k, coupled.salt).h1
+    );
+
+    let split = build(Some(7));
+    let bucket_salt = split.bucket_salt.expect("bucket salt recorded");
+    assert_eq!(split.key_hash(k).h1, KeyHash::from_key(k, bucket_salt).h1);
+    assert_eq!(split.key_hash(k).h2, KeyHash::from_key(k, split.salt).h2);
+    let idx: HashSet<u64> = keys.iter().map(|k| split.index_str(k)).collect();
+    assert_eq!(idx.len(), keys.len());
+    assert!(idx.iter().all(|&i| i < keys.len() as u64));
+}
@@ -0,0 +1,39 @@
+use minimal_perfect_hash::chd::{BuildConfig, Builder, MphError, bucket_histogram};
+
+#[test]
+fn empty_key_set_is_an_error() {
+    let r = Builder::new().build(std::iter::empty::<&[u8]>());
+    assert!(matches!(r, Err(MphError::EmptyKeySet)));
+    let r = bucket_histogram(std::iter::empty::<&[u8]>(), &BuildConfig::default());
+    assert!(matches!(r, Err(MphError::EmptyKeySet)));
+}
+
+#[test]
+fn zero_buckets_is_an_error() {
+    let cfg = BuildConfig {
+        buckets: Some(0),
+        ..Default::default()
+    };
+    let keys: [&[u8]; 2] = [b"a", b"b"];
+    let r = Builder::new().with_config(cfg.clone()).build(keys);
+    assert!(matches!(r, Err(MphError::InvalidConfig(_))));
+    assert!(matches!(
+        bucket_histogram(keys, &cfg),
+        Err(MphError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn single_key_single_bucket() {
+    let cfg = BuildConfig {
+        buckets: Some(1),
+        ..Default::default()
+    };
+    let mph = Builder::new()
+        .with_config(cfg)
+        .build([b"only" as &[u8]])
+        .unwrap();
+    assert_eq!(mph.n, 1);
+    assert_eq!(mph.buckets, 1);
+    assert_eq!(mph.index(b"only"), 0);
+}
@@ -0,0 +1,55 @@
+use minimal_perfect_hash::chd::{BuildConfig, Builder, Placement};
+
+fn cfg() -> BuildConfig {
+    BuildConfig {
+        placement: Placement::Mixed,
+        parallel_placement: true,
+        rehash_limit: 32,
+        ..Default::default()
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn build(keys: &[String]) -> minimal_perfect_hash::chd::Mphf {
+    Builder::new()
+        .with_config(cfg())
+        .build(keys.iter().map(|k| k.as_bytes()))
+        .unwrap()
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_placement_is_a_bijection_whatever_the_pool_size() {
+    let keys: Vec<String> = (0..20_000).map(|i| format!("pp-{i}")).collect();
+    let pool = |threads| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+    };
+    let one = pool(1).install(|| build(&keys));
+    let eight = pool(8).install(|| build(&keys));
+    let again = build(&keys);
+
+    let mut seen = vec![false; keys.len()];
+    for k in &keys {
+        let i = one.index(k.as_bytes()) as usize;
+        assert!(!seen[i], "index {i} assigned twice");
+        seen[i] = true;
+    }
+    for other in [&eight, &again] {
+        assert_eq!(other.salt, one.salt);
+        assert_eq!(other.disps, one.disps);
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+#[test]
+fn parallel_placement_needs_the_feature() {
+    use minimal_perfect_hash::chd::MphError;
+
+    let res = Builder::new()
+        .with_config(cfg())
+        .build([b"a".as_slice(), b"b"]);
+    assert!(matches!(res, Err(MphError::InvalidConfig(_))));
+}
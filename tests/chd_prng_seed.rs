@@ -0,0 +1,66 @@
+use minimal_perfect_hash::chd::{BuildConfig, Builder, Mphf, Placement};
+
+/// Per-round salt increment: round `r` of base `b` uses the same effective salt as
+/// round 0 of base `b ^ r * ROUND_STEP` (the round is spread by this constant before
+/// it is mixed into the base).
+const ROUND_STEP: u64 = 0x9E37_79B9_7F4A_7C15;
+
+fn keys() -> Vec<String> {
+    (0..1000).map(|i| format!("chd-{i}")).collect()
+}
+
+/// Tight enough that most salts fail, so builds need several rounds.
+fn cfg(salt: u64, prng_seed: Option<u64>) -> BuildConfig {
+    BuildConfig {
+        placement: Placement::Mixed,
+        max_random_attempts: Some(2048),
+        rehash_limit: 64,
+        salt,
+        prng_seed,
+        ..Default::default()
+    }
+}
+
+fn build(cfg: BuildConfig) -> (Mphf, u32) {
+    let (mph, stats) = Builder::new()
+        .with_config(cfg)
+        .build_with_stats(keys().iter().map(|k| k.as_bytes()))
+        .unwrap();
+    (mph, stats.round)
+}
+
+#[test]
+fn a_fixed_seed_searches_every_round_alike() {
+    let base = BuildConfig::default().salt;
+    let (late, round) = build(cfg(base, Some(42)));
+    assert!(round > 0, "the build should need a rehash");
+
+    // The same effective salt reached in round 0 searches with the same PRNG stream.
+    let shifted = base ^ (round as u64).wrapping_mul(ROUND_STEP);
+    let (early, first) = build(cfg(shifted, Some(42)));
+    assert_eq!(first, 0);
+    assert_eq!(early.salt(), late.salt());
+    assert_eq!(early.disps, late.disps);
+}
+
+#[test]
+fn the_same_seed_repeats_and_another_seed_differs() {
+    let base = BuildConfig::default().salt;
+    let (a, _) = build(cfg(base, Some(7)));
+    let (b, _) = build(cfg(base, Some(7)));
+    assert_eq!((a.salt(), &a.disps), (b.salt(), &b.disps));
+
+    // Another seed draws other random displacements for the same salt.
+    let loose = |prng_seed| BuildConfig {
+        max_random_attempts: None,
+        rehash_limit: 0,
+        ..cfg(base, prng_seed)
+    };
+    let (x, _) = build(loose(Some(1)));
+    let (y, _) = build(loose(Some(2)));
+    assert_eq!(x.salt(), y.salt());
+    assert_ne!(x.disps, y.disps);
+    for k in keys() {
+        assert!(x.index_str(&k) < 1000 && y.index_str(&k) < 1000);
+    }
+}
@@ -0,0 +1,49 @@
+use minimal_perfect_hash::chd::{BuildConfig, Builder, MphError, Placement};
+
+fn keys() -> Vec<String> {
+    (0..1000).map(|i| format!("chd-{i}")).collect()
+}
+
+fn cfg(max_random_attempts: Option<u32>, rehash_limit: u32) -> BuildConfig {
+    BuildConfig {
+        placement: Placement::Mixed,
+        max_random_attempts,
+        rehash_limit,
+        prng_seed: Some(42),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn a_small_cap_gives_up_on_the_salt_and_rehashes() {
+    let keys = keys();
+    let build = |cfg: BuildConfig| {
+        Builder::new()
+            .with_config(cfg)
+            .build_with_stats(keys.iter().map(|k| k.as_bytes()))
+    };
+
+    // Uncapped, the first salt places every bucket.
+    let (_, stats) = build(cfg(None, 0)).unwrap();
+    assert_eq!(stats.round, 0);
+
+    // Capped, that salt is abandoned: with no rounds left the build fails...
+    assert!(matches!(
+        build(cfg(Some(2048), 0)),
+        Err(MphError::Unresolvable)
+    ));
+    // ...and with rounds left it moves on to a later salt.
+    let (mph, stats) = build(cfg(Some(2048), 64)).unwrap();
+    assert!(stats.round > 0);
+    let mut seen = vec![false; keys.len()];
+    for k in &keys {
+        let i = mph.index_str(k) as usize;
+        assert!(!std::mem::replace(&mut seen[i], true), "index {i} twice");
+    }
+
+    // Linear scan only: no salt survives, every round failing without a random draw.
+    assert!(matches!(
+        build(cfg(Some(0), 4)),
+        Err(MphError::Unresolvable)
+    ));
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "serde")]
+
+use minimal_perfect_hash::chd::{BuildConfig, Builder, MphError, Mphf, Placement};
+use minimal_perfect_hash::util::PackedArray;
+
+fn build() -> (Vec<String>, Mphf) {
+    let keys: Vec<String> = (0..300).map(|i| format!("chd-serde-{i}")).collect();
+    let cfg = BuildConfig {
+        placement: Placement::Mixed,
+        ..Default::default()
+    };
+    let mph = Builder::new()
+        .with_config(cfg)
+        .build(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    (keys, mph)
+}
+
+#[test]
+fn round_trip_resolves_every_key() {
+    let (keys, mph) = build();
+    let back = Mphf::from_bytes(&mph.to_bytes().unwrap()).unwrap();
+    assert_eq!(back.disps, mph.disps);
+    for k in &keys {
+        assert_eq!(back.index(k.as_bytes()), mph.index(k.as_bytes()));
+    }
+}
+
+#[test]
+fn bucket_count_must_match_the_displacements() {
+    let (_, mph) = build();
+    for buckets in [0, mph.buckets - 1, mph.buckets + 1, 1 << 20] {
+        let bad = Mphf {
+            buckets,
+            ..mph.clone()
+        };
+        let res = Mphf::from_bytes(&bad.to_bytes().unwrap());
+        assert!(matches!(res, Err(MphError::Serde(_))), "buckets={buckets}");
+    }
+    let bad = Mphf {
+        n: 0,
+        ..mph.clone()
+    };
+    assert!(Mphf::from_bytes(&bad.to_bytes().unwrap()).is_err());
+}
+
+#[test]
+fn lookups_on_an_edited_bucket_count_panic_instead_of_reading_past_disps() {
+    let (keys, mph) = build();
+    let bad = Mphf {
+        buckets: 1 << 20,
+        ..mph
+    };
+    let res = std::panic::catch_unwind(|| {
+        for k in &keys {
+            bad.index(k.as_bytes());
+        }
+    });
+    assert!(res.is_err());
+}
+
+#[test]
+fn empty_displacements_are_rejected() {
+    let (_, mph) = build();
+    let bad = Mphf {
+        buckets: 0,
+        disps: PackedArray::new(0, 0),
+        ..mph
+    };
+    assert!(Mphf::from_bytes(&bad.to_bytes().unwrap()).is_err());
+}
@@ -0,0 +1,24 @@
+use minimal_perfect_hash::chd::{BuildConfig, Builder};
+
+#[test]
+fn space_stats_describe_the_packed_displacements() {
+    let keys: Vec<String> = (0..1000).map(|i| format!("sp-{i}")).collect();
+    let cfg = BuildConfig {
+        rehash_limit: 32,
+        ..Default::default()
+    };
+    let mph = Builder::new()
+        .with_config(cfg)
+        .build(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    let st = mph.space_stats();
+
+    assert_eq!(st.buckets, mph.buckets);
+    assert_eq!(st.disp_bits, mph.disps.width());
+    assert_eq!(64 - st.max_disp.leading_zeros(), st.disp_bits as u32);
+    assert!(st.avg_disp >= 0.0 && st.avg_disp <= st.max_disp as f64);
+    let packed = st.disp_bits as f64 * mph.buckets as f64 / 1000.0;
+    assert!((st.packed_bits_per_key - packed).abs() < 1e-9);
+    assert!(st.bits_per_key >= st.packed_bits_per_key);
+    assert!(st.bits_per_key < st.packed_bits_per_key + 64.0 / 1000.0);
+}
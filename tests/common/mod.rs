@@ -0,0 +1,22 @@
+//! Helpers shared by the integration tests; each test crate uses only some of them.
+#![allow(dead_code)]
+
+use minimal_perfect_hash::{BuildConfig, Builder, Mphf};
+
+/// `n` distinct keys `"{tag}-{i}"`.
+pub fn keys(tag: &str, n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("{tag}-{i}")).collect()
+}
+
+/// [`keys`] as owned byte strings.
+pub fn byte_keys(tag: &str, n: usize) -> Vec<Vec<u8>> {
+    keys(tag, n).into_iter().map(String::into_bytes).collect()
+}
+
+/// Build over `keys` under `cfg`, which must succeed.
+pub fn build<K: AsRef<[u8]>>(keys: &[K], cfg: BuildConfig) -> Mphf {
+    Builder::new()
+        .with_config(cfg)
+        .build(keys.iter().map(|k| k.as_ref()))
+        .unwrap()
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::{BuildConfig, MphError, Mphf, partition_of};
+use std::collections::HashSet;
+
+#[test]
+fn index_is_a_global_bijection() {
+    let keys = keys("concat", 3000);
+    let parts = 3;
+    let mut shards: Vec<Vec<&String>> = vec![Vec::new(); parts];
+    for k in &keys {
+        shards[partition_of(k.as_bytes(), parts)].push(k);
+    }
+    let built: Vec<Mphf> = shards
+        .iter()
+        .map(|s| build(s, BuildConfig::default()))
+        .collect();
+    let concat = Mphf::concat(&built).unwrap();
+    assert_eq!(concat.n(), keys.len() as u64);
+    assert_eq!(concat.range(), keys.len() as u64);
+    assert_eq!(concat.offsets().len(), parts + 1);
+
+    let idx: HashSet<u64> = keys.iter().map(|k| concat.index_str(k)).collect();
+    assert_eq!(idx.len(), keys.len());
+    assert!(idx.iter().all(|&i| i < concat.range()));
+}
+
+#[test]
+fn index_in_serves_parts_sharded_by_another_rule() {
+    let keys = keys("concat", 1000);
+    let (even, odd): (Vec<&String>, Vec<&String>) = keys.iter().partition(|k| k.len() % 2 == 0);
+    let non_minimal = BuildConfig {
+        require_minimal: false,
+        ..Default::default()
+    };
+    let built = [
+        build(&even, BuildConfig::default()),
+        build(&odd, non_minimal),
+    ];
+    let concat = Mphf::concat(&built).unwrap();
+    assert_eq!(concat.n(), keys.len() as u64);
+    assert_eq!(concat.range(), even.len() as u64 + built[1].range());
+    assert!(concat.range() > concat.n());
+
+    let mut seen = HashSet::new();
+    for (part, shard) in [&even, &odd].into_iter().enumerate() {
+        let (lo, hi) = (concat.offsets()[part], concat.offsets()[part + 1]);
+        for k in shard {
+            let i = concat.index_in(part, k.as_bytes());
+            assert!((lo..hi).contains(&i), "part {part}: {i} outside {lo}..{hi}");
+            assert!(seen.insert(i), "index {i} repeated");
+        }
+    }
+}
+
+#[test]
+fn empty_and_overflowing_concats_are_errors() {
+    assert!(matches!(Mphf::concat(&[]), Err(MphError::InvalidConfig(_))));
+
+    let keys = keys("concat", 100);
+    let mut huge = build(&keys.iter().collect::<Vec<_>>(), BuildConfig::default());
+    huge.n = u64::MAX;
+    assert!(matches!(
+        Mphf::concat(&[huge.clone(), huge]),
+        Err(MphError::InvalidConfig(_))
+    ));
+}
@@ -0,0 +1,37 @@
+use minimal_perfect_hash::Builder;
+use std::sync::Arc;
+use std::thread;
+
+const N_KEYS: usize = 20_000;
+const THREADS: usize = 16;
+const ROUNDS: usize = 8;
+
+#[test]
+fn concurrent_index_matches_single_threaded() {
+    let keys: Vec<String> = (0..N_KEYS).map(|i| format!("key_{i}")).collect();
+    let mph = Arc::new(
+        Builder::new()
+            .build(keys.iter().map(|k| k.as_bytes()))
+            .unwrap(),
+    );
+    let expected: Arc<Vec<u64>> = Arc::new(keys.iter().map(|k| mph.index_str(k)).collect());
+    let keys = Arc::new(keys);
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let (mph, keys, expected) = (mph.clone(), keys.clone(), expected.clone());
+            thread::spawn(move || {
+                for _ in 0..ROUNDS {
+                    // Each thread walks the keys from a different offset to interleave accesses.
+                    for j in 0..N_KEYS {
+                        let i = (j + t * N_KEYS / THREADS) % N_KEYS;
+                        assert_eq!(mph.index_str(&keys[i]), expected[i]);
+                    }
+                }
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+}
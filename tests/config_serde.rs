@@ -0,0 +1,48 @@
+#![cfg(feature = "serde")]
+
+use minimal_perfect_hash::{BuildConfig, Builder, SaltSchedule, chd};
+
+#[test]
+fn partial_bdz_config_fills_in_defaults() {
+    let cfg: BuildConfig =
+        serde_json::from_str(r#"{ "gamma": 1.3, "salt_schedule": "SplitMix" }"#).unwrap();
+    let default = BuildConfig::default();
+    assert_eq!(cfg.gamma, 1.3);
+    assert_eq!(cfg.salt_schedule, SaltSchedule::SplitMix);
+    assert_eq!(cfg.salt, default.salt);
+    assert_eq!(cfg.rehash_limit, default.rehash_limit);
+    assert_eq!(cfg.require_minimal, default.require_minimal);
+
+    let empty: BuildConfig = serde_json::from_str("{}").unwrap();
+    assert_eq!(format!("{empty:?}"), format!("{default:?}"));
+}
+
+#[test]
+fn bdz_config_round_trips_and_builds() {
+    let cfg = BuildConfig {
+        fingerprint: true,
+        salt: 42,
+        ..Default::default()
+    };
+    let back: BuildConfig = serde_json::from_str(&serde_json::to_string(&cfg).unwrap()).unwrap();
+    assert_eq!(format!("{back:?}"), format!("{cfg:?}"));
+
+    let keys = ["a", "b", "c"].map(str::as_bytes);
+    let mph = Builder::new().with_config(back).build(keys).unwrap();
+    assert!(mph.index_if_member(b"a").is_some());
+}
+
+#[test]
+fn partial_chd_config_fills_in_defaults() {
+    let cfg: chd::BuildConfig =
+        serde_json::from_str(r#"{ "placement": "Mixed", "bucket_salt": 7 }"#).unwrap();
+    let default = chd::BuildConfig::default();
+    assert_eq!(cfg.placement, chd::Placement::Mixed);
+    assert_eq!(cfg.bucket_salt, Some(7));
+    assert_eq!(cfg.salt, default.salt);
+    assert_eq!(cfg.target_bucket_size, default.target_bucket_size);
+
+    let back: chd::BuildConfig =
+        serde_json::from_str(&serde_json::to_string(&cfg).unwrap()).unwrap();
+    assert_eq!(format!("{back:?}"), format!("{cfg:?}"));
+}
@@ -0,0 +1,96 @@
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, Builder, FailedBuild, MIN_GAMMA_RANK3, MphError};
+
+fn keys() -> Vec<String> {
+    (0..2000).map(|i| format!("cb-{i}")).collect()
+}
+
+/// A single round at the peelability threshold, with a salt whose first five rounds
+/// fail on `keys()`.
+fn tight() -> BuildConfig {
+    BuildConfig {
+        gamma: MIN_GAMMA_RANK3,
+        salt: 13,
+        rehash_limit: 0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn escalation_never_retries_a_round() {
+    let keys = keys();
+    let keys = || keys.iter().map(|k| k.as_bytes());
+    let cfg = tight();
+    let mut result = Builder::new()
+        .with_config(cfg.clone())
+        .build_with_stats(keys());
+    let mut prior = cfg;
+    let mut next_round = 1;
+    while let Err(err) = &result {
+        let failed = FailedBuild::from_error(prior, err).expect("peeling failure");
+        assert_eq!(failed.last_round + 1, next_round);
+        prior = failed.cfg.clone();
+        next_round += 2;
+        let resumed = BuildConfig {
+            salt_round_start: failed.last_round + 1,
+            rehash_limit: 1,
+            ..failed.cfg.clone()
+        };
+        // `continue_build` is that config applied to a fresh build.
+        result = Builder::new().with_config(resumed).build_with_stats(keys());
+        let direct = Builder::new().continue_build(failed, 2, keys());
+        assert_eq!(direct.is_ok(), result.is_ok());
+        if let (Ok(a), Ok((b, _))) = (direct, &result) {
+            assert_eq!(a.salt(), b.salt());
+        }
+        assert!(next_round < 200, "no round peeled");
+    }
+    let (mph, stats) = result.unwrap();
+    assert_eq!((stats.round, next_round), (5, 7));
+    assert_minimal_perfect(&mph, &keys().collect::<Vec<_>>());
+}
+
+#[test]
+fn resumed_rounds_match_a_longer_build() {
+    let keys = keys();
+    let keys = || keys.iter().map(|k| k.as_bytes());
+    let long = BuildConfig {
+        rehash_limit: 64,
+        ..tight()
+    };
+    let (full, stats) = Builder::new()
+        .with_config(long)
+        .build_with_stats(keys())
+        .unwrap();
+    assert_eq!(stats.round, 5);
+    let prior = FailedBuild {
+        cfg: tight(),
+        last_round: stats.round - 1,
+    };
+    let resumed = Builder::new().continue_build(prior, 1, keys()).unwrap();
+    assert_eq!(resumed.salt(), full.salt());
+}
+
+#[test]
+fn only_peeling_failures_resume() {
+    let err = Builder::new().build([&b"a"[..], b"a"]).unwrap_err();
+    assert!(matches!(err, MphError::DuplicateKey));
+    assert!(FailedBuild::from_error(BuildConfig::default(), &err).is_none());
+
+    let prior = FailedBuild {
+        cfg: BuildConfig::default(),
+        last_round: 0,
+    };
+    let zero = Builder::new().continue_build(prior, 0, [&b"a"[..]]);
+    assert!(matches!(zero, Err(MphError::InvalidConfig(_))));
+}
+
+#[test]
+fn no_round_left_after_the_last() {
+    let prior = FailedBuild {
+        cfg: BuildConfig::default(),
+        last_round: u32::MAX,
+    };
+    let res = Builder::new().continue_build(prior, 1, [&b"a"[..]]);
+    assert!(matches!(res, Err(MphError::InvalidConfig(_))));
+}
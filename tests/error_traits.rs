@@ -0,0 +1,27 @@
+use minimal_perfect_hash::{Builder, MphError};
+use std::error::Error;
+
+fn _assert<T: Send + Sync + 'static>() {}
+
+#[test]
+fn mph_error_is_send_sync_static() {
+    _assert::<MphError>();
+}
+
+#[test]
+fn mph_error_converts_to_boxed_error() {
+    fn build() -> Result<(), Box<dyn Error + Send + Sync>> {
+        Builder::new().build([b"dup".as_slice(), b"dup".as_slice()])?;
+        Ok(())
+    }
+    let err = build().unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<MphError>(),
+        Some(MphError::DuplicateKey)
+    ));
+}
+
+#[test]
+fn chd_error_is_send_sync_static() {
+    _assert::<minimal_perfect_hash::chd::MphError>();
+}
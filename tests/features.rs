@@ -0,0 +1,16 @@
+// Integration tests see the crate's own feature set, so `cfg!` here matches the library.
+
+#[test]
+fn parallel_enabled_reflects_the_feature() {
+    assert_eq!(
+        minimal_perfect_hash::parallel_enabled(),
+        cfg!(feature = "parallel")
+    );
+}
+
+#[test]
+fn simd_enabled_requires_the_feature() {
+    if !cfg!(feature = "simd") || cfg!(feature = "checked") {
+        assert!(!minimal_perfect_hash::simd_enabled());
+    }
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, MphError};
+
+#[test]
+fn kept_keys_get_a_fresh_bijection() {
+    let owned = keys("filter", 3000);
+    let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_bytes()).collect();
+    let cfg = BuildConfig {
+        fingerprint: true,
+        ..Default::default()
+    };
+    let mph = build(&keys, cfg);
+    for modulus in [1, 2, 7, 150, 2999] {
+        let keep = |k: &[u8]| k.len().is_multiple_of(modulus) || k.ends_with(b"7");
+        let kept: Vec<&[u8]> = keys.iter().copied().filter(|k| keep(k)).collect();
+        let sub = mph.filter_rebuild(&keys, keep).unwrap();
+        assert!(sub.is_minimal());
+        assert_minimal_perfect(&sub, &kept);
+        // Fingerprints are carried over: kept keys are members.
+        assert!(kept.iter().all(|k| sub.index_if_member(k).is_some()));
+    }
+}
+
+#[test]
+fn non_minimal_builds_stay_non_minimal() {
+    let owned = keys("filter", 500);
+    let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_bytes()).collect();
+    let cfg = BuildConfig {
+        require_minimal: false,
+        ..Default::default()
+    };
+    let sub = build(&keys, cfg)
+        .filter_rebuild(&keys, |k| k[k.len() - 1] % 2 == 0)
+        .unwrap();
+    assert!(!sub.is_minimal());
+}
+
+#[test]
+fn keeping_nothing_is_an_error() {
+    for n in [5, 500] {
+        let owned = keys("filter", n);
+        let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_bytes()).collect();
+        let mph = build(&keys, BuildConfig::default());
+        let res = mph.filter_rebuild(&keys, |_| false);
+        assert!(matches!(res, Err(MphError::EmptyKeySet)), "n={n}");
+    }
+}
@@ -0,0 +1,64 @@
+use minimal_perfect_hash::Builder;
+use std::collections::HashSet;
+
+/// Key `i` of length `N`: `i` in the leading bytes (so keys are distinct), a
+/// pattern after them to reach every chunk and tail branch of the hash.
+fn key<const N: usize>(i: u64) -> [u8; N] {
+    let mut k = [0u8; N];
+    let lead = i.to_le_bytes();
+    for (j, b) in k.iter_mut().enumerate() {
+        *b = match lead.get(j) {
+            Some(&b) => b,
+            None => (i as u8).wrapping_mul(31).wrapping_add(j as u8 * 7),
+        };
+    }
+    k
+}
+
+fn check<const N: usize>() {
+    // Every distinct key there is for N <= 1, 200 of them otherwise.
+    let count = match N {
+        0 => 1,
+        1 => 256,
+        _ => 200,
+    };
+    let keys: Vec<[u8; N]> = (0..count).map(key::<N>).collect();
+    let mph = Builder::new().build_fixed(&keys).unwrap();
+
+    let mut seen = HashSet::new();
+    for k in &keys {
+        let i = mph.index_fixed(k);
+        assert_eq!(i, mph.index(&k[..]), "N={N} key {k:?}");
+        assert!(
+            i < count && seen.insert(i),
+            "N={N}: index {i} repeated or out of range"
+        );
+    }
+    if N >= 2 {
+        for j in count..count + 200 {
+            let k = key::<N>(j);
+            assert_eq!(
+                mph.index_fixed(&k),
+                mph.index(&k[..]),
+                "N={N} non-member {k:?}"
+            );
+        }
+    }
+}
+
+macro_rules! check_lengths {
+    ($($n:literal)*) => { $( check::<$n>(); )* };
+}
+
+#[test]
+fn index_fixed_matches_index() {
+    check_lengths!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20);
+    check_lengths!(23 24 25 31 32 33 47 48 49 63 64 65 95 96 97 100);
+    check_lengths!(127 128 129 200 255 256 257 1000);
+}
+
+#[test]
+fn build_fixed_rejects_duplicates() {
+    let keys = [[1u8; 16], [2; 16], [1; 16]];
+    assert!(Builder::new().build_fixed(&keys).is_err());
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::keys;
+use minimal_perfect_hash::{FrozenMap, MphError};
+
+fn payload(k: &[u8]) -> usize {
+    k.iter().map(|&b| b as usize).sum::<usize>() * 31 + k.len()
+}
+
+#[test]
+fn every_key_gets_its_payload() {
+    for n in [1, 20, 32, 5000] {
+        let keys = keys("entry", n);
+        let mut calls = 0;
+        let map = FrozenMap::build_with(&keys, |k| {
+            calls += 1;
+            payload(k)
+        })
+        .unwrap();
+        assert_eq!(calls, n, "payload runs once per key");
+        assert_eq!(map.len(), n);
+        assert_eq!(map.values().len(), n);
+        for k in &keys {
+            assert_eq!(*map.get_str(k), payload(k.as_bytes()), "{k}");
+            let i = map.mphf().index_str(k) as usize;
+            assert_eq!(map.values()[i], payload(k.as_bytes()));
+        }
+    }
+}
+
+#[test]
+fn duplicate_keys_are_rejected() {
+    for n in [10, 1000] {
+        let mut keys = keys("entry", n);
+        keys.push(keys[n / 2].clone());
+        let res = FrozenMap::build_with(&keys, payload);
+        assert!(matches!(res, Err(MphError::DuplicateKey)), "n={n}");
+    }
+}
+
+#[test]
+fn empty_key_set_is_an_error() {
+    let res = FrozenMap::build_with(&[] as &[&str], payload);
+    assert!(matches!(res, Err(MphError::EmptyKeySet)));
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::{BuildConfig, FrozenSet, MphError};
+
+#[test]
+fn members_are_always_found() {
+    let keys = keys("member", 5000);
+    for bits in [1, 8, 13, 64] {
+        let set = FrozenSet::build(&keys, bits).unwrap();
+        assert_eq!(set.len(), keys.len());
+        assert!(!set.is_empty());
+        assert_eq!(set.fingerprint_bits(), bits);
+        for k in &keys {
+            assert!(set.contains_str(k), "bits={bits} {k}");
+        }
+    }
+}
+
+#[test]
+fn false_positive_rate_is_about_two_to_the_minus_bits() {
+    let set = FrozenSet::build(&keys("member", 10_000), 8).unwrap();
+    let probes = 200_000;
+    let hits = (0..probes)
+        .filter(|i| set.contains_str(&format!("outsider-{i}")))
+        .count();
+    // Expected 200_000 / 256 ≈ 781; allow a wide margin around it.
+    assert!((500..1100).contains(&hits), "{hits} false positives");
+}
+
+#[test]
+fn width_64_rejects_non_members_and_width_1_lets_about_half_through() {
+    let keys = keys("member", 2000);
+    let wide = FrozenSet::build(&keys, 64).unwrap();
+    assert!((0..20_000).all(|i| !wide.contains_str(&format!("outsider-{i}"))));
+
+    let narrow = FrozenSet::build(&keys, 1).unwrap();
+    let hits = (0..20_000)
+        .filter(|i| narrow.contains_str(&format!("outsider-{i}")))
+        .count();
+    assert!((9000..11_000).contains(&hits), "{hits} false positives");
+}
+
+#[test]
+fn empty_input_is_an_error() {
+    assert!(matches!(
+        FrozenSet::build(&Vec::<String>::new(), 8),
+        Err(MphError::EmptyKeySet)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "FrozenSet needs a minimal MPH")]
+fn from_mphf_rejects_a_non_minimal_mph() {
+    let keys = keys("member", 500);
+    let cfg = BuildConfig {
+        require_minimal: false,
+        ..Default::default()
+    };
+    let mph = build(&keys, cfg);
+    assert!(!mph.is_minimal());
+    FrozenSet::from_mphf(mph, &keys, 8);
+}
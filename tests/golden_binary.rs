@@ -0,0 +1,43 @@
+#![cfg(feature = "binary")]
+
+use minimal_perfect_hash::{HASH_VERSION, MphError, Mphf};
+
+/// `to_binary` of the MPH in `golden/mphf_v1.bin` (keys `golden-0..golden-499`,
+/// fingerprints on). Never regenerate it: a failure here means old blobs stopped
+/// resolving.
+const GOLDEN_V1: &[u8] = include_bytes!("golden/mphb_v1.bin");
+const N_KEYS: u64 = 500;
+const VERSION_AT: usize = 4;
+
+fn key(i: u64) -> String {
+    format!("golden-{i}")
+}
+
+#[test]
+fn golden_v1_blob_still_resolves() {
+    assert_eq!(GOLDEN_V1[VERSION_AT] as u32, HASH_VERSION);
+    let mph = Mphf::from_binary(GOLDEN_V1).unwrap();
+    assert_eq!(mph.n, N_KEYS);
+    for i in 0..N_KEYS {
+        assert_eq!(mph.index_str(&key(i)), i, "key {i}");
+        assert_eq!(mph.index_if_member(key(i).as_bytes()), Some(i), "key {i}");
+    }
+}
+
+#[test]
+fn golden_v1_blob_reencodes_identically() {
+    let mph = Mphf::from_binary(GOLDEN_V1).unwrap();
+    assert_eq!(mph.to_binary(), GOLDEN_V1);
+}
+
+#[test]
+fn unknown_hash_version_is_rejected() {
+    for v in [0, 2, 0xFF] {
+        let mut blob = GOLDEN_V1.to_vec();
+        blob[VERSION_AT] = v;
+        assert!(
+            matches!(Mphf::from_binary(&blob), Err(MphError::UnsupportedHashVersion(x)) if x == v),
+            "version {v}"
+        );
+    }
+}
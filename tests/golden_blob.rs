@@ -0,0 +1,71 @@
+#![cfg(feature = "serde")]
+
+use minimal_perfect_hash::{MphError, Mphf};
+
+/// Written by `to_bytes` for keys `golden-0..golden-499` with fingerprints on.
+/// Never regenerate it: a failure here means old blobs stopped resolving.
+const GOLDEN_V1: &[u8] = include_bytes!("golden/mphf_v1.bin");
+const N_KEYS: u64 = 500;
+
+fn key(i: u64) -> String {
+    format!("golden-{i}")
+}
+
+#[test]
+fn golden_v1_blob_still_resolves() {
+    assert_eq!(GOLDEN_V1[0], 1);
+    let mph = Mphf::from_bytes(GOLDEN_V1).unwrap();
+    assert_eq!(mph.n, N_KEYS);
+    // Minimal builds number keys in build order, so each key maps to its position.
+    for i in 0..N_KEYS {
+        assert_eq!(mph.index_str(&key(i)), i, "key {i}");
+        assert_eq!(mph.index_if_member(key(i).as_bytes()), Some(i), "key {i}");
+    }
+}
+
+#[test]
+fn golden_v1_blob_reencodes_identically() {
+    let mph = Mphf::from_bytes(GOLDEN_V1).unwrap();
+    assert_eq!(mph.to_bytes().unwrap(), GOLDEN_V1);
+}
+
+#[test]
+fn unknown_hash_version_is_rejected() {
+    let mut blob = GOLDEN_V1.to_vec();
+    blob[0] = 0xFF;
+    assert!(matches!(
+        Mphf::from_bytes(&blob),
+        Err(MphError::UnsupportedHashVersion(0xFF))
+    ));
+}
+
+#[test]
+fn truncated_blobs_are_errors() {
+    for len in 0..GOLDEN_V1.len() {
+        assert!(Mphf::from_bytes(&GOLDEN_V1[..len]).is_err(), "prefix {len}");
+    }
+}
+
+// Field offsets in a version-1 blob: version byte, then bincode's fixed-width
+// `n: u64`, `m: u32`, `salt: u64` and the `u64` length of `g`.
+const M_AT: usize = 9;
+const G_LEN_AT: usize = 21;
+
+#[test]
+fn inconsistent_fields_are_corrupt() {
+    let mut blob = GOLDEN_V1.to_vec();
+    blob[1..9].copy_from_slice(&0u64.to_le_bytes()); // n = 0
+    assert!(matches!(Mphf::from_bytes(&blob), Err(MphError::Corrupt(_))));
+
+    let mut blob = GOLDEN_V1.to_vec();
+    let m = u32::from_le_bytes(blob[M_AT..M_AT + 4].try_into().unwrap());
+    blob[M_AT..M_AT + 4].copy_from_slice(&(m + 1).to_le_bytes()); // g.len() != m
+    assert!(matches!(Mphf::from_bytes(&blob), Err(MphError::Corrupt(_))));
+}
+
+#[test]
+fn bogus_length_fails_without_allocating_it() {
+    let mut blob = GOLDEN_V1.to_vec();
+    blob[G_LEN_AT..G_LEN_AT + 8].copy_from_slice(&(u64::MAX / 8).to_le_bytes());
+    assert!(matches!(Mphf::from_bytes(&blob), Err(MphError::Serde(_))));
+}
@@ -0,0 +1,42 @@
+use minimal_perfect_hash::{BuildConfig, Builder, MAX_KEYS, MphError, estimate_build_memory};
+
+#[test]
+fn key_counts_whose_g_sums_overflow_are_refused() {
+    // Three `g` entries below `n` must add up within u32.
+    assert!(3 * MAX_KEYS as u64 <= u32::MAX as u64);
+    assert!(3 * (MAX_KEYS as u64 + 1) > u32::MAX as u64);
+
+    // At gamma 1.0 `m` still fits in u32; only the key limit refuses the build.
+    assert_ne!(estimate_build_memory(MAX_KEYS, 1.0, 3), usize::MAX);
+    assert_eq!(estimate_build_memory(MAX_KEYS + 1, 1.0, 3), usize::MAX);
+    assert_eq!(estimate_build_memory(u32::MAX as usize, 1.0, 3), usize::MAX);
+}
+
+#[test]
+fn vertex_counts_beyond_u32_are_refused_before_allocating() {
+    let keys: Vec<String> = (0..255).map(|i| format!("limit-{i}")).collect();
+    let build = |cfg: BuildConfig| {
+        Builder::new()
+            .with_config(cfg)
+            .build(keys.iter().map(|k| k.as_bytes()))
+    };
+
+    let huge = BuildConfig {
+        gamma: 1e8,
+        ..Default::default()
+    };
+    assert!(matches!(build(huge), Err(MphError::TooManyVertices)));
+    assert_eq!(estimate_build_memory(255, 1e8, 3), usize::MAX);
+    assert_eq!(estimate_build_memory(usize::MAX, 1.27, 3), usize::MAX);
+
+    // 255 * 16_843_009 == u32::MAX, which fits, but no prime lies in [u32::MAX, u32::MAX].
+    let no_prime_left = BuildConfig {
+        gamma: 16_843_009.0,
+        prime_m: true,
+        ..Default::default()
+    };
+    assert!(matches!(
+        build(no_prime_left),
+        Err(MphError::TooManyVertices)
+    ));
+}
@@ -0,0 +1,36 @@
+//! Inputs of at least 2^17 keys, where the "parallel" feature switches duplicate
+//! detection to sharded parallel tables (the serial path runs without it).
+
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{Builder, MphError};
+
+const N: usize = (1 << 17) + 5000;
+
+fn keys() -> Vec<String> {
+    (0..N).map(|i| format!("large-{i:07}")).collect()
+}
+
+#[test]
+fn one_exact_duplicate_is_caught() {
+    for (dup_of, at) in [(0, N - 1), (N / 2, 17), (N - 1, N / 3)] {
+        let mut keys = keys();
+        keys.insert(at, keys[dup_of].clone());
+        let res = Builder::new().build(keys.iter().map(|k| k.as_bytes()));
+        assert!(
+            matches!(res, Err(MphError::DuplicateKey)),
+            "dup_of={dup_of}"
+        );
+    }
+}
+
+#[test]
+fn clean_build_is_a_bijection() {
+    let mut keys = keys();
+    // Keys that differ only in length still count as distinct.
+    keys.push("large-".into());
+    keys.push("large-0".into());
+    let mph = Builder::new()
+        .build(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    assert_minimal_perfect(&mph, &keys);
+}
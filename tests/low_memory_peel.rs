@@ -0,0 +1,35 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, Builder};
+
+fn cfg(low_memory_peel: bool) -> BuildConfig {
+    BuildConfig {
+        low_memory_peel,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn low_memory_peel_is_minimal_perfect() {
+    for n in [32, 1000, 100_000] {
+        let keys = keys("lm", n);
+        let mph = build(&keys, cfg(true));
+        assert_minimal_perfect(&mph, &keys);
+    }
+}
+
+#[test]
+fn edges_repeating_a_vertex_peel_like_csr() {
+    // Edges 0 and 1 list a vertex twice; edge 3 is only reachable once edge 2 is gone.
+    let (v0, v1, v2) = ([0, 1, 2, 5], [0, 3, 4, 5], [6, 7, 4, 1]);
+    let build = |low| {
+        Builder::new()
+            .with_config(cfg(low))
+            .build_from_vertices(&v0, &v1, &v2, 8, 1)
+            .unwrap()
+    };
+    let (csr, xor) = (build(false), build(true));
+    assert_eq!(xor.g, csr.g);
+}
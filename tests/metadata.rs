@@ -0,0 +1,44 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::{BuildConfig, Mphf};
+
+#[test]
+fn metadata_round_trips() {
+    let keys = keys("meta", 1000);
+    let mph = build(&keys, BuildConfig::default());
+    for meta in [&b""[..], b"schema=3;built=2026-10-15"] {
+        let blob = mph.to_bytes_with_metadata(meta).unwrap();
+        let (back, got) = Mphf::from_bytes_with_metadata(&blob).unwrap();
+        assert_eq!(got, meta);
+        for k in &keys {
+            assert_eq!(back.index_str(k), mph.index_str(k));
+        }
+
+        let mut streamed = Vec::new();
+        mph.write_to_with_metadata(&mut streamed, meta).unwrap();
+        assert_eq!(streamed, blob);
+        let (_, got) = Mphf::read_from_with_metadata(&streamed[..]).unwrap();
+        assert_eq!(got, meta);
+    }
+}
+
+#[test]
+fn metadata_section_is_optional() {
+    let mph = build(&keys("meta", 1000), BuildConfig::default());
+    // Old readers skip the section, new readers accept blobs without one.
+    let with = mph.to_bytes_with_metadata(b"v2").unwrap();
+    assert_eq!(Mphf::from_bytes(&with).unwrap().n, mph.n);
+    let (_, got) = Mphf::from_bytes_with_metadata(&mph.to_bytes().unwrap()).unwrap();
+    assert!(got.is_empty());
+}
+
+#[test]
+fn truncated_metadata_is_rejected() {
+    let mph = build(&keys("meta", 1000), BuildConfig::default());
+    let blob = mph.to_bytes_with_metadata(b"0123456789").unwrap();
+    assert!(Mphf::from_bytes_with_metadata(&blob[..blob.len() - 1]).is_err());
+    assert!(Mphf::read_from_with_metadata(&mph.to_bytes().unwrap()[..]).is_err());
+}
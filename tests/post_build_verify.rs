@@ -0,0 +1,69 @@
+use minimal_perfect_hash::{BuildConfig, Builder, MphError, Mphf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const N: usize = 1000;
+
+fn keys() -> Vec<String> {
+    (0..N).map(|i| format!("verify-{i}")).collect()
+}
+
+/// Roomy enough that the first salt peels, so keys are read exactly `N` times before
+/// verification.
+fn roomy() -> BuildConfig {
+    BuildConfig {
+        gamma: 1.5,
+        ..Default::default()
+    }
+}
+
+/// Build through a `get` that breaks its purity contract: once every key has been
+/// read (the first round's hashing), key `N - 1` reads as a copy of key 0.
+fn build_with_drift(keys: &[String], cfg: BuildConfig) -> Result<Mphf, MphError> {
+    let reads = AtomicUsize::new(0);
+    Builder::new().with_config(cfg).build_indexed(N, |i| {
+        let late = reads.fetch_add(1, Ordering::Relaxed) >= N;
+        let i = if late && i == N - 1 { 0 } else { i };
+        keys[i].as_bytes()
+    })
+}
+
+#[test]
+fn verification_reports_the_colliding_positions() {
+    let keys = keys();
+    let cfg = BuildConfig {
+        post_build_verify: true,
+        ..roomy()
+    };
+    let (mph, stats) = Builder::new()
+        .with_config(cfg.clone())
+        .build_with_stats(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    assert_eq!(stats.round, 0, "the drift needs the first round to peel");
+
+    match build_with_drift(&keys, cfg) {
+        Err(MphError::NotBijective {
+            index,
+            first,
+            second,
+        }) => {
+            assert_eq!((first, second), (0, N - 1));
+            assert_eq!(index, mph.index_str(&keys[0]));
+        }
+        other => panic!("expected NotBijective, got {other:?}"),
+    }
+}
+
+#[test]
+fn the_check_only_runs_when_enabled() {
+    let keys = keys();
+    assert!(build_with_drift(&keys, roomy()).is_ok());
+}
+
+#[test]
+fn honest_builds_pass_verification() {
+    let keys = keys();
+    let mph = Builder::new()
+        .build_checked(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    assert_eq!(mph.n, N as u64);
+}
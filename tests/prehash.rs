@@ -0,0 +1,52 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::{BuildConfig, Builder, MphError, prehash};
+
+#[test]
+fn prehashed_build_resolves_like_a_normal_build() {
+    for (n, minimal) in [(32, true), (1000, true), (1000, false), (30_000, true)] {
+        let owned = keys("prehash", n);
+        let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_bytes()).collect();
+        let cfg = BuildConfig {
+            require_minimal: minimal,
+            ..Default::default()
+        };
+        let normal = build(&keys, cfg.clone());
+        let (m, salt) = (normal.m as u64, normal.salt);
+        let (v0, v1, v2) = prehash(&keys, salt, m);
+        let pre = Builder::new()
+            .with_config(cfg)
+            .build_from_vertices(&v0, &v1, &v2, m, salt)
+            .unwrap();
+        assert_eq!((pre.n, pre.m as u64, pre.salt), (n as u64, m, salt));
+        for k in &keys {
+            assert_eq!(pre.index(k), normal.index(k), "n={n}");
+        }
+    }
+}
+
+#[test]
+fn inconsistent_vertices_are_rejected() {
+    let owned = keys("prehash", 100);
+    let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_bytes()).collect();
+    let m = 200;
+    let (v0, v1, v2) = prehash(&keys, 9, m);
+    let build =
+        |a: &[u32], b: &[u32], c: &[u32], m: u64| Builder::new().build_from_vertices(a, b, c, m, 9);
+    assert!(build(&v0, &v1, &v2, m).is_ok());
+
+    let mut out_of_range = v2.clone();
+    out_of_range[50] = m as u32;
+    let cases = [
+        build(&[], &[], &[], m),
+        build(&v0[..99], &v1, &v2, m),
+        build(&v0, &v1, &v2[1..], m),
+        build(&v0, &v1, &out_of_range, m),
+        build(&v0, &v1, &v2, 0),
+        build(&v0, &v1, &v2, u32::MAX as u64 + 1),
+    ];
+    for (i, res) in cases.into_iter().enumerate() {
+        assert!(matches!(res, Err(MphError::InvalidVertices)), "case {i}");
+    }
+}
@@ -0,0 +1,41 @@
+use minimal_perfect_hash::{BuildConfig, Builder};
+use std::collections::HashSet;
+
+fn is_prime(c: u64) -> bool {
+    c >= 2 && (2..).take_while(|d| d * d <= c).all(|d| !c.is_multiple_of(d))
+}
+
+#[test]
+fn stored_m_is_the_next_prime_and_lookups_round_trip() {
+    for (n, minimal) in [(32, true), (300, true), (5000, true), (5000, false)] {
+        let keys: Vec<String> = (0..n).map(|i| format!("prime-{i}")).collect();
+        let plain = BuildConfig {
+            require_minimal: minimal,
+            ..Default::default()
+        };
+        let prime = BuildConfig {
+            prime_m: true,
+            ..plain.clone()
+        };
+        let build = |cfg: BuildConfig| {
+            Builder::new()
+                .with_config(cfg)
+                .build(keys.iter().map(|k| k.as_bytes()))
+                .unwrap()
+        };
+        let (base, mph) = (build(plain), build(prime));
+        assert!(is_prime(mph.m as u64), "m = {}", mph.m);
+        assert!(mph.m >= base.m);
+        assert!(
+            (base.m..mph.m).all(|c| !is_prime(c as u64)),
+            "m skipped a prime"
+        );
+
+        let idx: HashSet<u64> = keys.iter().map(|k| mph.index_str(k)).collect();
+        assert_eq!(idx.len(), n);
+        assert!(idx.iter().all(|&i| i < mph.range()));
+        if minimal {
+            assert_eq!(mph.range(), n as u64);
+        }
+    }
+}
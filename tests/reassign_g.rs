@@ -0,0 +1,42 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::BuildConfig;
+use std::collections::HashSet;
+
+#[test]
+fn reassign_switches_minimality_on_the_same_graph() {
+    let owned = keys("rg", 2000);
+    let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_bytes()).collect();
+    let cfg = BuildConfig {
+        fingerprint: true,
+        ..Default::default()
+    };
+    let mph = build(&keys, cfg);
+
+    let wide = mph.reassign_g(&keys, false).unwrap();
+    assert!(!wide.is_minimal());
+    assert_eq!((wide.salt, wide.m), (mph.salt, mph.m));
+    let idx: HashSet<u64> = keys.iter().map(|k| wide.index(k)).collect();
+    assert_eq!(idx.len(), keys.len());
+    assert!(idx.iter().all(|&i| i < wide.range()));
+    assert!(keys.iter().all(|k| wide.index_if_member(k).is_some()));
+
+    let back = wide.reassign_g(&keys, true).unwrap();
+    assert_eq!(back.g, mph.g);
+    assert_eq!(back.fingerprints, mph.fingerprints);
+}
+
+#[test]
+fn reassign_drops_permutation() {
+    let owned = keys("rg", 10);
+    let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_bytes()).collect();
+    let mut mph = build(&keys, BuildConfig::default());
+    let plain: Vec<u64> = keys.iter().map(|k| mph.index(k)).collect();
+    let perm: Vec<u32> = (0..10).rev().collect();
+    mph.apply_permutation(&perm).unwrap();
+
+    let again = mph.reassign_g(&keys, true).unwrap();
+    let idx: Vec<u64> = keys.iter().map(|k| again.index(k)).collect();
+    assert_eq!(idx, plain);
+}
@@ -0,0 +1,40 @@
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{Builder, MphError};
+
+#[test]
+fn duplicates_are_reported_against_the_first_occurrence() {
+    let mut keys: Vec<String> = (0..1000).map(|i| format!("report-{i}")).collect();
+    // "report-3" appears at 3, 1000 and 1002; "report-999" at 999 and 1001.
+    keys.push("report-3".into());
+    keys.push("report-999".into());
+    keys.push("report-3".into());
+    let (mph, dups) = Builder::new().build_report_duplicates(keys.iter().map(|k| k.as_bytes()));
+    assert_eq!(dups, [(3, 1000), (999, 1001), (3, 1002)]);
+
+    let mph = mph.unwrap();
+    assert_eq!(mph.n, 1000);
+    assert_minimal_perfect(&mph, &keys[..1000]);
+}
+
+#[test]
+fn clean_input_reports_nothing() {
+    let keys: Vec<String> = (0..20).map(|i| format!("k{i}")).collect();
+    let (mph, dups) = Builder::new().build_report_duplicates(keys.iter().map(|k| k.as_bytes()));
+    assert!(dups.is_empty());
+    assert_eq!(mph.unwrap().n, 20);
+}
+
+#[test]
+fn every_copy_of_one_key_leaves_a_single_key() {
+    let keys = vec![b"same".as_slice(); 5];
+    let (mph, dups) = Builder::new().build_report_duplicates(keys);
+    assert_eq!(dups, [(0, 1), (0, 2), (0, 3), (0, 4)]);
+    assert_eq!(mph.unwrap().n, 1);
+}
+
+#[test]
+fn empty_input_is_an_error() {
+    let (mph, dups) = Builder::new().build_report_duplicates(Vec::<&[u8]>::new());
+    assert!(matches!(mph, Err(MphError::EmptyKeySet)));
+    assert!(dups.is_empty());
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "binary")]
+
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::{BuildConfig, MphError, Mphf, MphfHeader};
+
+/// Copy `bytes` into u64 storage so the blob starts 8-byte aligned.
+fn aligned(bytes: &[u8]) -> Vec<u64> {
+    let mut buf = vec![0u64; bytes.len().div_ceil(8)];
+    // Safety: `buf` spans at least `bytes.len()` bytes.
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), buf.as_mut_ptr().cast(), bytes.len()) };
+    buf
+}
+
+#[test]
+fn round_trip_resolves_every_key() {
+    for n in [1, 31, 32, 5000] {
+        let keys = keys("repr-c", n);
+        let mph = build(&keys, BuildConfig::default());
+        let blob = mph.to_bytes_repr_c().unwrap();
+        assert_eq!(blob.len(), 40 + 4 * mph.m as usize);
+        let back = Mphf::from_bytes_repr_c(&blob).unwrap();
+        for k in &keys {
+            assert_eq!(back.index(k.as_bytes()), mph.index(k.as_bytes()), "n={n}");
+        }
+    }
+}
+
+#[test]
+fn view_borrows_header_and_g() {
+    let mph = build(&keys("repr-c", 1000), BuildConfig::default());
+    let blob = mph.to_bytes_repr_c().unwrap();
+    let buf = aligned(&blob);
+    // Safety: `buf` holds at least `blob.len()` initialized bytes.
+    let bytes = unsafe { std::slice::from_raw_parts(buf.as_ptr().cast::<u8>(), blob.len()) };
+    let (header, g) = MphfHeader::view(bytes).unwrap();
+    assert_eq!(&header.magic, b"MPHC");
+    assert_eq!(
+        (header.n, header.m, header.g_len),
+        (mph.n, mph.m, mph.m as u64)
+    );
+    assert_eq!(header.salt, mph.salt());
+    assert_eq!(header.flags & 1, 1);
+    assert_eq!(g.as_ptr().cast::<u8>(), bytes[40..].as_ptr());
+    assert_eq!(g.len(), mph.m as usize);
+
+    let misaligned = aligned(&[&[0u8][..], &blob].concat());
+    // Safety: as above, one byte longer.
+    let shifted =
+        unsafe { std::slice::from_raw_parts(misaligned.as_ptr().cast::<u8>().add(1), blob.len()) };
+    assert!(matches!(
+        MphfHeader::view(shifted),
+        Err(MphError::Binary(_))
+    ));
+}
+
+#[test]
+fn malformed_blobs_are_rejected() {
+    let mph = build(&keys("repr-c", 100), BuildConfig::default());
+    let blob = mph.to_bytes_repr_c().unwrap();
+    let corrupt = |at: usize, byte: u8| {
+        let mut b = blob.clone();
+        b[at] ^= byte;
+        Mphf::from_bytes_repr_c(&b)
+    };
+    assert!(corrupt(0, 0xFF).is_err()); // magic
+    assert!(corrupt(4, 0xFF).is_err()); // version
+    assert!(corrupt(20, 0x02).is_err()); // unknown flag
+    assert!(corrupt(16, 0x01).is_err()); // m != g_len
+    for len in [0, 39, 40, blob.len() - 1] {
+        assert!(
+            Mphf::from_bytes_repr_c(&blob[..len]).is_err(),
+            "prefix {len}"
+        );
+    }
+    let mut longer = blob.clone();
+    longer.extend_from_slice(&[0; 4]);
+    assert!(Mphf::from_bytes_repr_c(&longer).is_err());
+}
+
+#[test]
+fn other_versions_are_unsupported() {
+    let mph = build(&keys("repr-c", 100), BuildConfig::default());
+    let blob = mph.to_bytes_repr_c().unwrap();
+    // The header holds a u32; versions past u8::MAX report as u8::MAX.
+    for (version, reported) in [(0u32, 0u8), (2, 2), (255, 255), (256, 255), (u32::MAX, 255)] {
+        let mut b = blob.clone();
+        b[4..8].copy_from_slice(&version.to_le_bytes());
+        assert!(matches!(
+            Mphf::from_bytes_repr_c(&b),
+            Err(MphError::UnsupportedHashVersion(v)) if v == reported
+        ));
+    }
+}
+
+#[test]
+fn fingerprints_do_not_fit_the_layout() {
+    let cfg = BuildConfig {
+        fingerprint: true,
+        ..Default::default()
+    };
+    let mph = build(&keys("repr-c", 100), cfg);
+    assert!(matches!(mph.to_bytes_repr_c(), Err(MphError::Binary(_))));
+}
@@ -0,0 +1,78 @@
+//! `m == n` marks a small set's sorted hash table, so no other MPH may have that shape.
+
+mod common;
+
+use common::keys;
+#[cfg(any(feature = "serde", feature = "binary"))]
+use minimal_perfect_hash::{BuildConfig, Mphf};
+use minimal_perfect_hash::{Builder, MphError, prehash};
+
+/// A graph-backed MPH (`m > n`) over 100 keys.
+#[cfg(any(feature = "serde", feature = "binary"))]
+fn graph_mph() -> Mphf {
+    let mph = common::build(&keys("shape", 100), BuildConfig::default());
+    assert!(mph.g_slice().is_some() && mph.m > 100);
+    mph
+}
+
+/// `blob` with the little-endian `n` at `at` overwritten by `m`.
+#[cfg(any(feature = "serde", feature = "binary"))]
+fn with_n_equal_to_m(mut blob: Vec<u8>, at: usize, m: u32) -> Vec<u8> {
+    blob[at..at + 8].copy_from_slice(&(m as u64).to_le_bytes());
+    blob
+}
+
+#[test]
+fn build_from_vertices_rejects_m_up_to_n() {
+    let owned = keys("shape", 100);
+    let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_bytes()).collect();
+    for m in [50, 99, 100] {
+        let (v0, v1, v2) = prehash(&keys, 3, m);
+        let res = Builder::new().build_from_vertices(&v0, &v1, &v2, m, 3);
+        assert!(matches!(res, Err(MphError::InvalidVertices)), "m={m}");
+    }
+}
+
+#[cfg(feature = "binary")]
+#[test]
+fn binary_decoders_reject_m_equal_to_n_for_large_sets() {
+    let mph = graph_mph();
+    let mphb = with_n_equal_to_m(mph.to_binary(), 6, mph.m);
+    assert!(matches!(Mphf::from_binary(&mphb), Err(MphError::Binary(_))));
+
+    let repr_c = with_n_equal_to_m(mph.to_bytes_repr_c().unwrap(), 8, mph.m);
+    assert!(matches!(
+        Mphf::from_bytes_repr_c(&repr_c),
+        Err(MphError::Binary(_))
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_decoder_rejects_m_equal_to_n_for_large_sets() {
+    let mph = graph_mph();
+    // A version byte, then bincode's fixed-width `n`.
+    let blob = with_n_equal_to_m(mph.to_bytes().unwrap(), 1, mph.m);
+    assert!(matches!(Mphf::from_bytes(&blob), Err(MphError::Corrupt(_))));
+}
+
+#[cfg(any(feature = "serde", feature = "binary"))]
+#[test]
+fn small_tables_still_decode() {
+    let keys = keys("shape", 20);
+    let mph = common::build(&keys, BuildConfig::default());
+    assert_eq!(mph.m as u64, mph.n);
+    let mut decoded = Vec::<Mphf>::new();
+    #[cfg(feature = "binary")]
+    {
+        decoded.push(Mphf::from_binary(&mph.to_binary()).unwrap());
+        decoded.push(Mphf::from_bytes_repr_c(&mph.to_bytes_repr_c().unwrap()).unwrap());
+    }
+    #[cfg(feature = "serde")]
+    decoded.push(Mphf::from_bytes(&mph.to_bytes().unwrap()).unwrap());
+    for back in decoded {
+        for k in &keys {
+            assert_eq!(back.index_str(k), mph.index_str(k));
+        }
+    }
+}
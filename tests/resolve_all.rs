@@ -0,0 +1,53 @@
+mod common;
+
+use common::{build, byte_keys};
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, Builder};
+
+#[test]
+fn resolve_all_is_a_permutation_in_input_order() {
+    // Below and above the small-set threshold, and with a `g` large enough to prefetch.
+    for n in [5, 1000, 300_000] {
+        let keys = byte_keys("ra", n);
+        let mph = build(&keys, BuildConfig::default());
+        let all = mph.resolve_all(&keys);
+        assert_eq!(all.len(), n);
+        for (k, &i) in keys.iter().zip(&all) {
+            assert_eq!(i, mph.index(k));
+        }
+        assert_minimal_perfect(&mph, &keys);
+    }
+}
+
+#[test]
+fn resolve_all_matches_index_for_non_members() {
+    let mph = Builder::new()
+        .build(["a", "b", "c"].map(str::as_bytes))
+        .unwrap();
+    let probes = ["x", "", "a", "zz"];
+    let all = mph.resolve_all(&probes);
+    let expected: Vec<u64> = probes.iter().map(|p| mph.index_str(p)).collect();
+    assert_eq!(all, expected);
+}
+
+#[test]
+fn index_pair_matches_two_index_calls() {
+    for (n, minimal) in [(5, true), (1000, true), (1000, false)] {
+        let keys: Vec<Vec<u8>> = (0..n).map(|i| format!("ip-{i}").into_bytes()).collect();
+        let cfg = minimal_perfect_hash::BuildConfig {
+            require_minimal: minimal,
+            ..Default::default()
+        };
+        let mph = Builder::new()
+            .with_config(cfg)
+            .build(keys.iter().map(|k| k.as_slice()))
+            .unwrap();
+        for p in keys
+            .windows(2)
+            .chain([&[b"absent".to_vec(), keys[0].clone()][..]])
+        {
+            let pair = mph.index_pair(&p[0], &p[1]);
+            assert_eq!(pair, (mph.index(&p[0]), mph.index(&p[1])), "n={n}");
+        }
+    }
+}
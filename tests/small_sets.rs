@@ -0,0 +1,102 @@
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, Builder};
+
+#[test]
+fn tiny_sets_are_bijective() {
+    // Several key families per size so degenerate graphs get exercised.
+    for n in 1..=8 {
+        for family in 0..64 {
+            let keys: Vec<String> = (0..n).map(|i| format!("f{family}_k{i}")).collect();
+            let mph = Builder::new()
+                .build(keys.iter().map(|k| k.as_bytes()))
+                .unwrap_or_else(|e| panic!("n={n} family={family}: {e}"));
+            assert_minimal_perfect(&mph, &keys);
+        }
+    }
+}
+
+#[test]
+fn tiny_sets_build_in_first_round() {
+    for n in 1..=2 {
+        let keys: Vec<String> = (0..n).map(|i| format!("key_{i}")).collect();
+        let (_, stats) = Builder::new()
+            .build_with_stats(keys.iter().map(|k| k.as_bytes()))
+            .unwrap();
+        assert_eq!(stats.round, 0, "n={n}");
+    }
+}
+
+#[test]
+fn sizes_around_small_table_threshold() {
+    // Below 32 keys the MPH is a sorted hash table; above it, a BDZ graph.
+    for n in 1..=40 {
+        let keys: Vec<String> = (0..n).map(|i| format!("t_{i}")).collect();
+        let mph = Builder::new()
+            .build(keys.iter().map(|k| k.as_bytes()))
+            .unwrap();
+        assert_minimal_perfect(&mph, &keys);
+        // Foreign keys still land in range.
+        for j in 0..100 {
+            let other = format!("other_{j}");
+            assert!(mph.index_str(&other) < n as u64, "n={n}");
+            assert!(mph.index_untrusted(other.as_bytes()).is_some(), "n={n}");
+        }
+    }
+}
+
+#[test]
+fn max_degree_comes_from_the_winning_attempt() {
+    let keys: Vec<String> = (0..10).map(|i| format!("key_{i}")).collect();
+    let (_, stats) = Builder::new()
+        .build_with_stats(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    assert_eq!(stats.max_degree, 0);
+
+    let keys: Vec<String> = (0..10_000).map(|i| format!("key_{i}")).collect();
+    let (_, stats) = Builder::new()
+        .build_with_stats(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    // Mean degree is 3n/m ≈ 2.4; a sound hash keeps the tail short.
+    assert!((1..=20).contains(&stats.max_degree), "{}", stats.max_degree);
+    if stats.round == 0 {
+        assert_eq!(stats.max_degree, stats.degrees.max);
+    }
+}
+
+#[test]
+fn configured_gamma_holds_from_256_keys() {
+    for n in [500usize, 1000] {
+        let keys: Vec<String> = (0..n).map(|i| format!("g_{i}")).collect();
+        for cfg in [BuildConfig::default(), BuildConfig::compact()] {
+            let gamma = cfg.gamma;
+            let (mph, stats) = Builder::new()
+                .with_config(cfg)
+                .build_with_stats(keys.iter().map(|k| k.as_bytes()))
+                .unwrap();
+            assert_eq!(
+                mph.m,
+                (gamma * n as f64).ceil() as u32,
+                "n={n} gamma={gamma}"
+            );
+            assert!(!stats.padded, "n={n} gamma={gamma}");
+        }
+    }
+}
+
+#[test]
+fn sets_below_256_keys_are_padded_to_gamma_1_5() {
+    let keys: Vec<String> = (0..100).map(|i| format!("p_{i}")).collect();
+    let (mph, stats) = Builder::new()
+        .build_with_stats(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    assert_eq!(mph.m, 150);
+    assert!(stats.padded);
+
+    // A `gamma` above the floor is used as configured.
+    let (mph, stats) = Builder::new()
+        .with_gamma(2.0)
+        .build_with_stats(keys.iter().map(|k| k.as_bytes()))
+        .unwrap();
+    assert_eq!(mph.m, 200);
+    assert!(!stats.padded);
+}
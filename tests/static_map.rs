@@ -0,0 +1,134 @@
+use minimal_perfect_hash::util::FrontCoded;
+use minimal_perfect_hash::{MphError, StaticMap};
+use std::collections::HashMap;
+
+fn words(n: usize) -> Vec<String> {
+    (0..n)
+        .map(|i| format!("/usr/share/dict/words/{i:06}"))
+        .collect()
+}
+
+#[test]
+fn front_coded_map_matches_plain_map() {
+    let keys = words(5000);
+    let entries = || keys.iter().enumerate().map(|(i, k)| (k.as_bytes(), i));
+    let plain = StaticMap::new(entries()).unwrap();
+    // Reversed input: the build sorts it.
+    let coded = StaticMap::new_front_coded(entries().rev()).unwrap();
+
+    for (i, k) in keys.iter().enumerate() {
+        assert_eq!(plain.get(k.as_bytes()), Some(&i));
+        assert_eq!(coded.get(k.as_bytes()), Some(&i));
+    }
+    for probe in [
+        "",
+        "/usr/share/dict/words/",
+        "/usr/share/dict/words/005000",
+        "x",
+    ] {
+        assert_eq!(coded.get(probe.as_bytes()), None, "{probe}");
+    }
+    for i in 0..coded.len() {
+        let key = coded.get_key(i);
+        assert_eq!(coded.get(&key), Some(&coded.frozen_map().values()[i]));
+        assert_eq!(plain.get_key(i), plain.iter().nth(i).unwrap().0);
+    }
+    assert!(coded.iter().all(|(k, v)| keys[*v].as_bytes() == &*k));
+    assert!(
+        coded.key_bytes() * 3 < plain.key_bytes(),
+        "{} vs {}",
+        coded.key_bytes(),
+        plain.key_bytes()
+    );
+}
+
+#[test]
+fn front_coded_handles_tiny_and_prefix_keys() {
+    let one = StaticMap::new_front_coded([("only", 1)]).unwrap();
+    assert_eq!(one.get(b"only"), Some(&1));
+    assert_eq!(&*one.get_key(0), b"only");
+
+    let keys = ["", "a", "ab", "abc", "abd", "b", "ba"];
+    let map = StaticMap::new_front_coded(keys.iter().map(|k| (k.as_bytes(), k.len()))).unwrap();
+    for k in keys {
+        assert_eq!(map.get(k.as_bytes()), Some(&k.len()), "{k:?}");
+    }
+    assert_eq!(map.get(b"abe"), None);
+}
+
+#[test]
+fn front_coded_list_round_trips_across_buckets() {
+    let keys: Vec<Vec<u8>> = (0..100u32)
+        .map(|i| (i / 7).to_be_bytes().repeat(1 + i as usize % 3))
+        .collect();
+    let fc = FrontCoded::new(&keys);
+    assert_eq!(fc.len(), keys.len());
+    for (i, k) in keys.iter().enumerate() {
+        assert_eq!(&fc.get(i), k, "{i}");
+    }
+    assert!(fc.iter().eq(keys.iter().cloned()));
+    assert!(FrontCoded::new(Vec::<&[u8]>::new()).is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn front_coded_deserialization_checks_the_blob() {
+    let fc = FrontCoded::new(["apple", "applet", "apply"]);
+    let json = serde_json::to_string(&fc).unwrap();
+    assert_eq!(serde_json::from_str::<FrontCoded>(&json).unwrap(), fc);
+
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["blob"].as_array_mut().unwrap().pop();
+    assert!(serde_json::from_value::<FrontCoded>(value.clone()).is_err());
+    value["len"] = 40.into();
+    assert!(serde_json::from_value::<FrontCoded>(value).is_err());
+}
+
+#[test]
+fn plain_map_answers_members_only() {
+    let keys = words(1000);
+    let map = StaticMap::new(keys.iter().enumerate().map(|(i, k)| (k.as_bytes(), i))).unwrap();
+    assert_eq!(map.len(), keys.len());
+    assert!(!map.is_empty());
+    for (i, k) in keys.iter().enumerate() {
+        assert_eq!(map.get(k.as_bytes()), Some(&i));
+        assert!(map.contains_key(k.as_bytes()));
+    }
+    for j in 1000..3000 {
+        let other = format!("/usr/share/dict/words/{j:06}");
+        assert_eq!(map.get(other.as_bytes()), None, "{other}");
+        assert!(!map.contains_key(other.as_bytes()));
+    }
+    let mut seen: Vec<usize> = map
+        .iter()
+        .map(|(k, &v)| {
+            assert_eq!(&*k, keys[v].as_bytes());
+            v
+        })
+        .collect();
+    seen.sort_unstable();
+    assert!(seen.into_iter().eq(0..keys.len()));
+}
+
+#[test]
+fn try_from_hash_map() {
+    let source: HashMap<Vec<u8>, u32> = (0..100u32)
+        .map(|i| (format!("k{i}").into_bytes(), i))
+        .collect();
+    let map = StaticMap::try_from(source.clone()).unwrap();
+    assert_eq!(map.len(), source.len());
+    for (k, v) in &source {
+        assert_eq!(map.get(k), Some(v));
+    }
+}
+
+#[test]
+fn empty_and_duplicate_inputs_are_errors() {
+    let empty = StaticMap::try_from(HashMap::<Vec<u8>, u32>::new());
+    assert!(matches!(empty, Err(MphError::EmptyKeySet)));
+    let empty = StaticMap::<u32>::new_front_coded(Vec::<(&[u8], u32)>::new());
+    assert!(matches!(empty, Err(MphError::EmptyKeySet)));
+
+    let dup = StaticMap::new([("a", 1), ("b", 2), ("a", 3)]);
+    assert!(matches!(dup, Err(MphError::DuplicateKey)));
+}
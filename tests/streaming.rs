@@ -0,0 +1,68 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, MphError, StreamingBuilder};
+
+#[test]
+fn finish_is_a_bijection_matching_builder() {
+    for n in [1, 31, 32, 1000, 20_000] {
+        let keys = keys("stream", n);
+        let mut sb = StreamingBuilder::new();
+        for k in &keys {
+            sb.push(k.as_bytes()).unwrap();
+        }
+        assert_eq!(sb.len(), n);
+        let mph = sb.finish().unwrap();
+        assert_eq!(mph.n, n as u64);
+        assert_minimal_perfect(&mph, &keys);
+
+        let direct = build(&keys, BuildConfig::default());
+        assert_eq!(
+            (mph.m, mph.salt, &mph.g),
+            (direct.m, direct.salt, &direct.g)
+        );
+    }
+}
+
+#[test]
+fn duplicate_push_is_rejected_and_changes_nothing() {
+    let keys = keys("stream", 2000);
+    let cfg = BuildConfig {
+        fingerprint: true,
+        ..Default::default()
+    };
+    let mut clean = StreamingBuilder::new().with_config(cfg.clone());
+    let mut dirty = StreamingBuilder::new().with_config(cfg);
+    for (i, k) in keys.iter().enumerate() {
+        clean.push(k.as_bytes()).unwrap();
+        dirty.push(k.as_bytes()).unwrap();
+        if i % 100 == 7 {
+            let before = dirty.len();
+            assert!(matches!(
+                dirty.push(keys[i / 2].as_bytes()),
+                Err(MphError::DuplicateKey)
+            ));
+            assert_eq!(dirty.len(), before);
+        }
+    }
+    // Prefixes and extensions of a pushed key are distinct keys.
+    for extra in ["stream-1".as_bytes(), b"stream-", b"stream-10000"] {
+        let fresh = !keys.iter().any(|k| k.as_bytes() == extra);
+        assert_eq!(dirty.push(extra).is_ok(), fresh);
+        if fresh {
+            clean.push(extra).unwrap();
+        }
+    }
+    assert_eq!(dirty.len(), clean.len());
+    let (a, b) = (clean.finish().unwrap(), dirty.finish().unwrap());
+    assert_eq!((a.m, a.salt, &a.g), (b.m, b.salt, &b.g));
+    assert_eq!(a.fingerprints, b.fingerprints);
+}
+
+#[test]
+fn finish_without_keys_is_an_error() {
+    let empty = StreamingBuilder::new();
+    assert!(empty.is_empty());
+    assert!(matches!(empty.finish(), Err(MphError::EmptyKeySet)));
+}
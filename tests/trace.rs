@@ -0,0 +1,41 @@
+#![cfg(feature = "debug-trace")]
+
+use minimal_perfect_hash::{BuildConfig, try_build_debug};
+
+fn degrees_of(edges: &[[u32; 3]], live: &[u32], m: u32) -> Vec<u32> {
+    let mut deg = vec![0; m as usize];
+    for &e in live {
+        for v in edges[e as usize] {
+            deg[v as usize] += 1;
+        }
+    }
+    deg
+}
+
+#[test]
+fn peeled_vertices_end_at_zero() {
+    let keys: Vec<String> = (0..500).map(|i| format!("trace-{i}")).collect();
+    let refs: Vec<&[u8]> = keys.iter().map(|k| k.as_bytes()).collect();
+    let cfg = BuildConfig {
+        gamma: 1.5,
+        ..Default::default()
+    };
+    let trace = try_build_debug(&refs, &cfg).unwrap();
+    assert!(trace.is_peelable());
+    assert_eq!(trace.peel_order.len(), keys.len());
+    assert!(trace.final_degrees.iter().all(|&d| d == 0));
+}
+
+#[test]
+fn only_the_two_core_keeps_a_degree() {
+    // A duplicate key is two identical edges, which never peel.
+    let mut keys: Vec<String> = (0..500).map(|i| format!("trace-{i}")).collect();
+    keys.push(keys[7].clone());
+    let refs: Vec<&[u8]> = keys.iter().map(|k| k.as_bytes()).collect();
+    let trace = try_build_debug(&refs, &BuildConfig::default()).unwrap();
+    assert!(!trace.is_peelable());
+    assert!(trace.unpeeled.contains(&7) && trace.unpeeled.contains(&500));
+    let expected = degrees_of(&trace.edges, &trace.unpeeled, trace.m);
+    assert_eq!(trace.final_degrees, expected);
+    assert!(trace.final_degrees.iter().all(|&d| d == 0 || d >= 2));
+}
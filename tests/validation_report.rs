@@ -0,0 +1,65 @@
+mod common;
+
+use common::{build, byte_keys};
+use minimal_perfect_hash::{BuildConfig, IndexCollision};
+
+#[test]
+fn build_set_covers_range() {
+    for n in [1, 20, 5000] {
+        let owned = byte_keys("vr", n);
+        let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_slice()).collect();
+        let mph = build(&keys, BuildConfig::default());
+        let report = mph.validate_against(&keys);
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(report.checked, n);
+        assert_eq!(report.min_index, Some(0));
+        assert_eq!(report.max_index, Some(n as u64 - 1));
+        assert_eq!(mph.index_range(), 0..n as u64);
+        assert!(!mph.contains_index(n as u64));
+    }
+}
+
+#[test]
+fn collision_and_partial_coverage_are_reported() {
+    let owned = byte_keys("vr", 1000);
+    let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_slice()).collect();
+    let mph = build(&keys, BuildConfig::default());
+
+    let mut dup = keys.clone();
+    dup.insert(10, keys[3]);
+    let report = mph.validate_against(&dup);
+    assert!(!report.is_ok());
+    assert_eq!(
+        report.first_collision,
+        Some(IndexCollision {
+            index: mph.index(keys[3]),
+            first: 3,
+            second: 10,
+        })
+    );
+    assert_eq!(report.checked, 11);
+
+    let report = mph.validate_against(&keys[..999]);
+    assert_eq!(report.first_collision, None);
+    assert!(!report.covers_range);
+
+    let report = mph.validate_against(&[]);
+    assert_eq!((report.checked, report.min_index), (0, None));
+}
+
+#[test]
+fn non_minimal_build_never_covers_range() {
+    let owned = byte_keys("vr", 1000);
+    let keys: Vec<&[u8]> = owned.iter().map(|k| k.as_slice()).collect();
+    let cfg = BuildConfig {
+        require_minimal: false,
+        ..Default::default()
+    };
+    let mph = build(&keys, cfg);
+    let report = mph.validate_against(&keys);
+    assert_eq!(report.first_collision, None);
+    assert!(!report.covers_range);
+    assert!(report.max_index.is_some_and(|i| mph.contains_index(i)));
+    assert!(mph.contains_index(1000) && !mph.contains_index(mph.range()));
+    assert_eq!(mph.index_range(), 0..mph.m as u64);
+}
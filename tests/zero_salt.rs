@@ -0,0 +1,64 @@
+mod common;
+
+use common::{build, keys};
+use minimal_perfect_hash::testing::assert_minimal_perfect;
+use minimal_perfect_hash::{BuildConfig, Builder, SaltSchedule, chd};
+use std::collections::HashSet;
+
+#[test]
+fn bdz_zero_salt_builds_and_round_trips() {
+    for schedule in [SaltSchedule::Fnv, SaltSchedule::SplitMix] {
+        for n in [10, 1000, 20_000] {
+            let keys = keys("zero-salt", n);
+            let cfg = BuildConfig {
+                salt: 0,
+                salt_schedule: schedule,
+                ..Default::default()
+            };
+            let (mph, stats) = Builder::new()
+                .with_config(cfg)
+                .build_with_stats(keys.iter().map(|k| k.as_bytes()))
+                .unwrap();
+            assert_ne!(mph.salt(), 0, "{schedule:?} n={n}");
+            assert_minimal_perfect(&mph, &keys);
+            if n >= 32 {
+                assert!(!stats.degrees.skewed, "{schedule:?} n={n}");
+            }
+        }
+    }
+}
+
+#[test]
+fn bdz_zero_salt_is_deterministic() {
+    let keys = keys("zero-salt", 5000);
+    let cfg = BuildConfig {
+        salt: 0,
+        ..Default::default()
+    };
+    let (a, b) = (build(&keys, cfg.clone()), build(&keys, cfg));
+    assert_eq!(a.salt(), b.salt());
+    assert!(keys.iter().all(|k| a.index_str(k) == b.index_str(k)));
+}
+
+#[test]
+fn chd_zero_salt_builds_and_round_trips() {
+    let keys = keys("zero-salt", 1000);
+    for bucket_salt in [None, Some(0)] {
+        // Linear placement fails most single attempts at this size, whatever the salt;
+        // leave room to rehash.
+        let cfg = chd::BuildConfig {
+            salt: 0,
+            bucket_salt,
+            rehash_limit: 32,
+            ..Default::default()
+        };
+        let mph = chd::Builder::new()
+            .with_config(cfg)
+            .build(keys.iter().map(|k| k.as_bytes()))
+            .unwrap();
+        assert_ne!(mph.salt(), 0);
+        let idx: HashSet<u64> = keys.iter().map(|k| mph.index(k.as_bytes())).collect();
+        assert_eq!(idx.len(), keys.len());
+        assert!(idx.iter().all(|&i| i < keys.len() as u64));
+    }
+}